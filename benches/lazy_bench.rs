@@ -0,0 +1,47 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::sync::Arc;
+use wildbird::prelude::*;
+
+static ARC_VALUE: Lazy<u64> = Lazy::new(|| 42);
+static INLINE_VALUE: LazyValue<u64> = LazyValue::new(|| 42);
+
+#[var]
+fn arc_var() -> String {
+    "wildbird".to_string()
+}
+
+#[var(no_arc)]
+fn inline_var() -> String {
+    "wildbird".to_string()
+}
+
+fn deref_hot_loop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("deref 1000x");
+    group.bench_function("Lazy<u64>", |b| {
+        b.iter(|| (0..1000).fold(0u64, |sum, _| sum.wrapping_add(*black_box(&ARC_VALUE).to_ref())))
+    });
+    group.bench_function("LazyValue<u64>", |b| {
+        b.iter(|| (0..1000).fold(0u64, |sum, _| sum.wrapping_add(**black_box(&INLINE_VALUE))))
+    });
+    group.bench_function("#[var] String", |b| {
+        b.iter(|| (0..1000).fold(0, |sum, _| sum + black_box(&ARC_VAR).len()))
+    });
+    group.bench_function("#[var(no_arc)] String", |b| {
+        b.iter(|| (0..1000).fold(0, |sum, _| sum + black_box(&INLINE_VAR).len()))
+    });
+    group.finish();
+}
+
+fn handout(c: &mut Criterion) {
+    let mut group = c.benchmark_group("handout");
+    group.bench_function("Lazy::instance() Arc clone", |b| {
+        b.iter(|| -> Arc<String> { black_box(&ARC_VAR).instance() })
+    });
+    group.bench_function("LazyValue &'static", |b| {
+        b.iter(|| -> &'static String { black_box(&INLINE_VAR).to_ref() })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, deref_hot_loop, handout);
+criterion_main!(benches);
@@ -12,6 +12,6 @@ pub fn parse_tokens<T: Parse>(q: TokenStream2) -> T {
 #[macro_export]
 macro_rules! q {
         ($($tt:tt)*) => {{
-            crate::_test_utils::parse_tokens(quote!($($tt)*))
+            $crate::_test_utils::parse_tokens(quote!($($tt)*))
         }};
 }
@@ -2,7 +2,11 @@ use proc_macro::TokenStream;
 use quote::{quote, quote_spanned, ToTokens, TokenStreamExt};
 use std::collections::HashMap;
 use std::str::FromStr;
-use syn::Visibility;
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::token::Comma;
+use syn::{Expr, ExprLit, FnArg, Lit, Meta, Visibility};
 use syn::__private::TokenStream2;
 
 pub fn is_public(vis: &Visibility) -> bool {
@@ -16,34 +20,139 @@ pub fn get_public_token(vis: &Visibility) -> TokenStream2 {
     }
 }
 
-pub fn parse_attr_to_map(attr: TokenStream) -> HashMap<String, String> {
-    let mut map: HashMap<String, String> = HashMap::new();
-    for entry in attr.to_string().split(',') {
-        let key_value = entry.split('=').collect::<Vec<&str>>();
-        let key = key_value.get(0).unwrap_or(&"");
-        let value = key_value.get(1).unwrap_or(&"");
-        map.insert(
-            key.trim().to_string(),
-            value.trim().replace('\"', "").to_string(),
-        );
+/// Injected call arguments for each typed (non `self`) function input
+pub fn inject_args(inputs: &Punctuated<FnArg, Comma>) -> TokenStream2 {
+    let mut args = quote!();
+    for arg in inputs.iter() {
+        if let FnArg::Typed(_) = arg {
+            if args.is_empty() {
+                args.append_all(quote!(wildbird::private::inject()))
+            } else {
+                args.append_all(quote!(, wildbird::private::inject()))
+            }
+        }
     }
-    map
+    args
 }
 
-pub struct CompileErrors {
-    count: i32,
-    quote: TokenStream2,
+pub enum AttrValue {
+    Flag,
+    Str(String),
+    List(Vec<String>),
+    Expr(TokenStream2),
+}
+
+/// Attribute arguments: `flag, key = "value", key = ["a", "b"], key = expr`
+#[derive(Default)]
+pub struct AttrMap(HashMap<String, AttrValue>);
+
+impl AttrMap {
+    pub fn parse(attr: TokenStream) -> syn::Result<AttrMap> {
+        let metas = Punctuated::<Meta, Comma>::parse_terminated.parse(attr)?;
+        let mut map = HashMap::new();
+        for meta in metas {
+            let key = meta.path().to_token_stream().to_string();
+            let value = match meta {
+                Meta::Path(_) => AttrValue::Flag,
+                Meta::List(list) => return Err(syn::Error::new(list.span(), "expected: key = value")),
+                Meta::NameValue(entry) => match entry.value {
+                    Expr::Lit(ExprLit { lit: Lit::Str(value), .. }) => AttrValue::Str(value.value()),
+                    Expr::Array(array) => {
+                        let mut values = vec![];
+                        for item in array.elems.iter() {
+                            match item {
+                                Expr::Lit(ExprLit { lit: Lit::Str(value), .. }) => values.push(value.value()),
+                                _ => return Err(syn::Error::new(item.span(), "expected string literal")),
+                            }
+                        }
+                        AttrValue::List(values)
+                    }
+                    value => AttrValue::Expr(value.to_token_stream()),
+                },
+            };
+            map.insert(key, value);
+        }
+        Ok(AttrMap(map))
+    }
+
+    /// Parsed map, parse error is added to `errors`
+    pub fn parse_or_default(attr: TokenStream, errors: &mut CompileErrors) -> AttrMap {
+        match Self::parse(attr) {
+            Ok(map) => map,
+            Err(e) => {
+                errors.add_spaned(e.span(), format!("Invalid attribute: {e}"));
+                AttrMap::default()
+            }
+        }
+    }
+
+    pub fn has(&self, key: &str) -> bool {
+        self.0.contains_key(key)
+    }
+
+    pub fn str(&self, key: &str) -> Option<String> {
+        match self.0.get(key)? {
+            AttrValue::Str(value) => Some(value.clone()),
+            AttrValue::Expr(value) => Some(value.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Value as expression tokens, string literal or e.g. `concat!(..)`
+    pub fn expr(&self, key: &str) -> Option<TokenStream2> {
+        match self.0.get(key)? {
+            AttrValue::Str(value) => Some(value.to_token_stream()),
+            AttrValue::Expr(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn list(&self, key: &str) -> Option<Vec<String>> {
+        match self.0.get(key)? {
+            AttrValue::List(values) => Some(values.clone()),
+            AttrValue::Str(value) => Some(vec![value.clone()]),
+            _ => None,
+        }
+    }
 }
 
-impl Default for CompileErrors {
-    fn default() -> Self {
-        Self {
-            count: 0,
-            quote: Default::default(),
+/// `init_policy = "retry" | "fail_fast"` as `Lazy` builder call
+pub fn init_policy(map: &AttrMap, errors: &mut CompileErrors) -> TokenStream2 {
+    match map.str("init_policy").as_deref() {
+        None | Some("retry") => quote!(),
+        Some("fail_fast") => quote!( .with_policy(wildbird::InitPolicy::FailFast) ),
+        Some(policy) => {
+            errors.add(format!(
+                "Invalid init_policy: \"{policy}\"\n\texpected: init_policy = \"retry\" | \"fail_fast\""
+            ));
+            quote!()
         }
     }
 }
 
+/// Duration in milliseconds: `"500ms" | "30s" | "5m" | "1h" | "1d"`
+pub fn duration_millis(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit())?;
+    let (amount, unit) = value.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+    let unit = match unit.trim() {
+        "ms" => 1,
+        "s" => 1000,
+        "m" => 60 * 1000,
+        "h" => 60 * 60 * 1000,
+        "d" => 24 * 60 * 60 * 1000,
+        _ => return None,
+    };
+    amount.checked_mul(unit)
+}
+
+#[derive(Default)]
+pub struct CompileErrors {
+    count: i32,
+    quote: TokenStream2,
+}
+
 impl ToTokens for CompileErrors {
     fn to_tokens(&self, tokens: &mut TokenStream2) {
         tokens.append_all(self.quote.clone())
@@ -61,13 +170,13 @@ impl CompileErrors {
     pub fn add(&mut self, message: String) {
         let msg = format!("[wildbird]\n{message}\n");
         self.quote.append_all(quote!( compile_error!(#msg); ));
-        self.count = self.count + 1;
+        self.count += 1;
     }
     pub fn add_spaned(&mut self, span: syn::__private::Span, message: String) {
         let msg = format!("[wildbird]\n{message}\n");
         self.quote
             .append_all(quote_spanned!( span => compile_error!(#msg); ));
-        self.count = self.count + 1;
+        self.count += 1;
     }
 
     pub fn has_errors(&self) -> bool {
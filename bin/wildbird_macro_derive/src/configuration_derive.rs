@@ -0,0 +1,147 @@
+use crate::_utils::*;
+use proc_macro::TokenStream;
+use quote::{format_ident, quote, quote_spanned, ToTokens, TokenStreamExt};
+use syn::{
+    spanned::Spanned, Ident, ImplItem, ImplItemFn, ItemImpl, ReturnType, Type,
+    __private::TokenStream2,
+};
+
+#[inline]
+fn _impl_configuration_static(config_static: &Ident, config_type: &TokenStream2) -> TokenStream2 {
+    let construct = quote_spanned! { config_type.span() =>
+        wildbird::private::configuration_construct::<#config_type>()
+    };
+    quote! {
+        #[doc(hidden)]
+        #[allow(non_upper_case_globals)]
+        static #config_static: wildbird::Lazy<#config_type> = #construct;
+    }
+}
+
+// AppConfig -> APP_CONFIG, HTTPConfig -> HTTP_CONFIG
+fn _upper_snake(ident: &Ident) -> String {
+    let chars: Vec<char> = ident.to_string().trim_start_matches('_').chars().collect();
+    let mut name = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 && !name.ends_with('_') {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if !prev.is_uppercase() || next_lower {
+                name.push('_');
+            }
+        }
+        name.push(c.to_ascii_uppercase());
+    }
+    name
+}
+
+fn _impl_bean(
+    method: &ImplItemFn,
+    config_ident: &Ident,
+    config_static: &Ident,
+    config_type: &TokenStream2,
+    beans: &mut Vec<String>,
+    errors: &mut CompileErrors,
+) -> TokenStream2 {
+    let method_name = &method.sig.ident;
+    let service_type = match &method.sig.output {
+        ReturnType::Default => {
+            errors.add_spaned(
+                method.sig.span(),
+                format!("#[configuration] - Specify return type for: {method_name}()"),
+            );
+            return TokenStream2::default();
+        }
+        ReturnType::Type(_, t) => t.to_token_stream(),
+    };
+    // One binding per type, Inject() couldn't choose between two methods
+    let type_key = service_type.to_string();
+    if beans.contains(&type_key) {
+        errors.add_spaned(
+            method.sig.output.span(),
+            format!("#[configuration] - {method_name}() returns already configured type: {type_key}"),
+        );
+        return TokenStream2::default();
+    }
+    beans.push(type_key);
+
+    let args = inject_args(&method.sig.inputs);
+    let call = match method.sig.receiver() {
+        Some(_) => quote!( #config_static.#method_name(#args) ),
+        None => quote!( #config_type::#method_name(#args) ),
+    };
+    let body = match method.sig.asyncness {
+        Some(_) => quote!( wildbird::private::block(async { #call.await }) ),
+        None => call,
+    };
+
+    // Lazy id is taken from the caller location, span it at the method
+    let construct = quote_spanned! { method_name.span() =>
+        wildbird::private::lazy_construct(|| #body)
+    };
+    let static_name = format_ident!("{}_{}", _upper_snake(config_ident), method_name.to_string().to_uppercase());
+    let visibility_token = get_public_token(&method.vis);
+    quote! {
+        #[allow(non_upper_case_globals)]
+        #visibility_token static #static_name: wildbird::Lazy<#service_type> = #construct;
+        wildbird::private::auto_register!(#static_name);
+
+        impl wildbird::private::PrivateService<#service_type, #config_type> for wildbird::private::Arc<#service_type> {
+            fn inject() -> Self { #static_name.instance() }
+        }
+
+        impl wildbird::private::PrivateService<#service_type, #config_type> for wildbird::Lazy<#service_type> {
+            fn inject() -> Self { #static_name.clone_lazy() }
+        }
+    }
+}
+
+pub fn main(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let Ok(config) = syn::parse::<ItemImpl>(item.clone()) else {
+        let mut errors = CompileErrors::default();
+        errors.add("#[configuration] - expected impl block".to_string());
+        return errors.into();
+    };
+
+    let source = TokenStream2::from(item);
+    let mut errors = CompileErrors::default();
+
+    let config_ident = match config.self_ty.as_ref() {
+        Type::Path(path) if config.generics.params.is_empty() && config.trait_.is_none() => {
+            path.path.segments.last().map(|s| s.ident.clone())
+        }
+        _ => None,
+    };
+    let Some(config_ident) = config_ident else {
+        errors.add_spaned(
+            config.self_ty.span(),
+            "#[configuration] - expected inherent impl of non generic type".to_string(),
+        );
+        return quote!( #errors #source ).into();
+    };
+
+    let config_type = config.self_ty.to_token_stream();
+    let config_static = format_ident!("_{}_configuration", config_ident);
+    let mut beans = _impl_configuration_static(&config_static, &config_type);
+    let mut bean_types = Vec::new();
+    for item in config.items.iter() {
+        if let ImplItem::Fn(method) = item {
+            beans.append_all(_impl_bean(
+                method,
+                &config_ident,
+                &config_static,
+                &config_type,
+                &mut bean_types,
+                &mut errors,
+            ));
+        }
+    }
+
+    let res = quote! {
+        #errors
+        #source
+        #[automatically_derived]
+        #beans
+    };
+    res.into()
+}
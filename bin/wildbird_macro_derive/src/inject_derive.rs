@@ -0,0 +1,72 @@
+use crate::_utils::*;
+use proc_macro::TokenStream;
+use quote::{quote, TokenStreamExt};
+use syn::{spanned::Spanned, FnArg, ItemFn, Type, __private::TokenStream2};
+
+fn _take_inject_attr(arg: &mut FnArg) -> bool {
+    let FnArg::Typed(arg) = arg else {
+        return false;
+    };
+    let Some(index) = arg.attrs.iter().position(|a| a.path().is_ident("inject")) else {
+        return false;
+    };
+    arg.attrs.remove(index);
+    true
+}
+
+fn _impl_inject_fn(mut inject_fn: ItemFn, errors: &mut CompileErrors) -> TokenStream2 {
+    let mut injected = quote!();
+    let mut inputs = inject_fn.sig.inputs.clone();
+    inputs.clear();
+
+    for mut arg in inject_fn.sig.inputs.into_pairs().map(|pair| pair.into_value()) {
+        if !_take_inject_attr(&mut arg) {
+            inputs.push(arg);
+            continue;
+        }
+        let FnArg::Typed(arg) = arg else {
+            continue;
+        };
+        if let Type::ImplTrait(_) = arg.ty.as_ref() {
+            errors.add_spaned(
+                arg.ty.span(),
+                "#[inject] - 'impl Trait' parameter can't be injected".to_string(),
+            );
+        }
+        let pat = &arg.pat;
+        let ty = &arg.ty;
+        injected.append_all(quote!( let #pat: #ty = wildbird::private::inject(); ));
+    }
+
+    if injected.is_empty() {
+        errors.add_spaned(
+            inject_fn.sig.ident.span(),
+            format!("#[inject] - mark injected parameters of {}() with #[inject]", inject_fn.sig.ident),
+        );
+    }
+
+    inject_fn.sig.inputs = inputs;
+    let block = &inject_fn.block;
+    let body = quote!({
+        #injected
+        #block
+    });
+    inject_fn.block = Box::new(syn::parse2(body).expect("valid fn block"));
+    quote!( #inject_fn )
+}
+
+pub fn main(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let Ok(inject_fn) = syn::parse::<ItemFn>(item) else {
+        let mut errors = CompileErrors::default();
+        errors.add("#[inject] - expected fn".to_string());
+        return errors.into();
+    };
+
+    let mut errors = CompileErrors::default();
+    let inject_fn = _impl_inject_fn(inject_fn, &mut errors);
+    let res = quote! {
+        #errors
+        #inject_fn
+    };
+    res.into()
+}
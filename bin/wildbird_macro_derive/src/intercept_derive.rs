@@ -0,0 +1,149 @@
+use crate::_utils::*;
+use proc_macro::TokenStream;
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    GenericArgument, ImplItem, ImplItemFn, ItemImpl, Path, PathArguments, ReturnType, Token, Type,
+    __private::TokenStream2,
+};
+
+struct InterceptAttr {
+    pub with: Vec<Path>,
+}
+
+impl Parse for InterceptAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key: syn::Ident = input.parse()?;
+        if key != "with" {
+            return Err(syn::Error::new(key.span(), "expected: with = Interceptor, ..."));
+        }
+        input.parse::<Token![=]>()?;
+        let with = Punctuated::<Path, Token![,]>::parse_separated_nonempty(input)?;
+        Ok(InterceptAttr {
+            with: with.into_iter().collect(),
+        })
+    }
+}
+
+// `impl Trait` is not allowed in closure return type, also when nested
+fn _has_impl_trait(ty: &Type) -> bool {
+    match ty {
+        Type::ImplTrait(_) => true,
+        Type::Array(t) => _has_impl_trait(&t.elem),
+        Type::Group(t) => _has_impl_trait(&t.elem),
+        Type::Paren(t) => _has_impl_trait(&t.elem),
+        Type::Ptr(t) => _has_impl_trait(&t.elem),
+        Type::Reference(t) => _has_impl_trait(&t.elem),
+        Type::Slice(t) => _has_impl_trait(&t.elem),
+        Type::Tuple(t) => t.elems.iter().any(_has_impl_trait),
+        Type::Path(t) => t.path.segments.iter().any(|s| match &s.arguments {
+            PathArguments::AngleBracketed(args) => args.args.iter().any(|arg| match arg {
+                GenericArgument::Type(t) => _has_impl_trait(t),
+                _ => false,
+            }),
+            PathArguments::Parenthesized(args) => {
+                args.inputs.iter().any(_has_impl_trait)
+                    || matches!(&args.output, ReturnType::Type(_, t) if _has_impl_trait(t))
+            }
+            PathArguments::None => false,
+        }),
+        _ => false,
+    }
+}
+
+fn _intercept_method(method: &mut ImplItemFn, service_name: &str, interceptors: &[Path]) {
+    let method_name = method.sig.ident.to_string();
+    let block = &method.block;
+    let output = match &method.sig.output {
+        ReturnType::Default => quote!(()),
+        ReturnType::Type(_, t) => t.to_token_stream(),
+    };
+
+    let mut before = quote!();
+    let mut after = quote!();
+    let mut on_error = quote!();
+    for (index, _) in interceptors.iter().enumerate().rev() {
+        let index = syn::Index::from(index);
+        after.append_all(quote!( wildbird::intercept::Interceptor::after(&__interceptors.#index, &__call); ));
+        on_error.append_all(quote!( wildbird::intercept::Interceptor::on_error(&__interceptors.#index, &__call, __error); ));
+    }
+    for (index, _) in interceptors.iter().enumerate() {
+        let index = syn::Index::from(index);
+        before.append_all(quote!( wildbird::intercept::Interceptor::before(&__interceptors.#index, &__call); ));
+    }
+
+    let has_impl_trait = match &method.sig.output {
+        ReturnType::Type(_, t) => _has_impl_trait(t),
+        ReturnType::Default => false,
+    };
+    let result = match (method.sig.asyncness.is_some(), has_impl_trait) {
+        (true, false) => quote!( wildbird::intercept::returning::<#output, _>(async move #block).await ),
+        (true, true) => quote!( async move #block.await ),
+        (false, false) => quote!( (move || -> #output #block)() ),
+        (false, true) => quote!( (move || #block)() ),
+    };
+    // Resolved by type, also for `io::Result` and other aliases
+    let check_error = quote! {
+        #[allow(unused_imports)]
+        use wildbird::intercept::{ErrorDebug as _, ErrorOpaque as _, NoError as _};
+        if let Some(__error) = (&&&wildbird::intercept::ErrorProbe(&__result)).error() { #on_error }
+    };
+
+    let new_block = quote! {{
+        static __INTERCEPTORS: wildbird::intercept::Interceptors<( #( #interceptors, )* )> =
+            wildbird::intercept::Interceptors::new();
+        let __call = wildbird::intercept::Call::new(#service_name, #method_name);
+        let __interceptors = __INTERCEPTORS.get();
+        #before
+        let __result = #result;
+        #check_error
+        #after
+        __result
+    }};
+    method.block = syn::parse2(new_block).expect("valid intercepted block");
+}
+
+pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut errors = CompileErrors::default();
+    let Ok(mut service_impl) = syn::parse::<ItemImpl>(item.clone()) else {
+        errors.add("#[intercept] - expected impl block".to_string());
+        return errors.into();
+    };
+
+    let attribute = match syn::parse::<InterceptAttr>(attr.clone()) {
+        Ok(attribute) => attribute,
+        Err(err) => {
+            errors.add_spaned(
+                err.span(),
+                format!("#[intercept] - {err}\n\texpected: #[intercept(with = Interceptor, ...)]"),
+            );
+            let source = TokenStream2::from(item);
+            return quote!( #errors #source ).into();
+        }
+    };
+
+    let service_name = match service_impl.self_ty.as_ref() {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|s| s.ident.to_string())
+            .unwrap_or_default(),
+        other => other.to_token_stream().to_string(),
+    };
+
+    for item in service_impl.items.iter_mut() {
+        if let ImplItem::Fn(method) = item {
+            if method.sig.receiver().is_some() {
+                _intercept_method(method, &service_name, &attribute.with);
+            }
+        }
+    }
+
+    let res = quote! {
+        #errors
+        #service_impl
+    };
+    res.into()
+}
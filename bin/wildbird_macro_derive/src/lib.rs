@@ -6,9 +6,21 @@ use proc_macro::TokenStream;
 #[doc(hidden)]
 mod _utils;
 #[doc(hidden)]
+mod configuration_derive;
+#[doc(hidden)]
+mod inject_derive;
+#[doc(hidden)]
+mod intercept_derive;
+#[doc(hidden)]
 mod service_derive;
 #[doc(hidden)]
+mod service_mock_derive;
+#[doc(hidden)]
+mod test_derive;
+#[doc(hidden)]
 mod var_derive;
+#[doc(hidden)]
+mod verify_derive;
 
 /// Service annotation
 #[proc_macro_attribute]
@@ -22,5 +34,41 @@ pub fn var(attr: TokenStream, item: TokenStream) -> TokenStream {
     var_derive::main(attr, item)
 }
 
+/// Configuration annotation
+#[proc_macro_attribute]
+pub fn configuration(attr: TokenStream, item: TokenStream) -> TokenStream {
+    configuration_derive::main(attr, item)
+}
+
+/// Intercept annotation
+#[proc_macro_attribute]
+pub fn intercept(attr: TokenStream, item: TokenStream) -> TokenStream {
+    intercept_derive::main(attr, item)
+}
+
+/// Inject annotation, parameters marked with `#[inject]` are resolved with `Inject()`
+#[proc_macro_attribute]
+pub fn inject(attr: TokenStream, item: TokenStream) -> TokenStream {
+    inject_derive::main(attr, item)
+}
+
+/// Service mock annotation, mock is generated with `testing` feature
+#[proc_macro_attribute]
+pub fn service_mock(attr: TokenStream, item: TokenStream) -> TokenStream {
+    service_mock_derive::main(attr, item)
+}
+
+/// Test with isolated service graph, re-exported as `wildbird::testing::test`
+#[proc_macro_attribute]
+pub fn wildbird_test(attr: TokenStream, item: TokenStream) -> TokenStream {
+    test_derive::main(attr, item)
+}
+
+/// Test verifying registered service graph
+#[proc_macro_attribute]
+pub fn verify_test(attr: TokenStream, item: TokenStream) -> TokenStream {
+    verify_derive::main(attr, item)
+}
+
 #[cfg(test)]
 mod _test_utils;
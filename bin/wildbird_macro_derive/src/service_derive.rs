@@ -1,34 +1,45 @@
 use crate::_utils::*;
 use proc_macro::TokenStream;
-use quote::{format_ident, quote, ToTokens, TokenStreamExt};
+use quote::{format_ident, quote, quote_spanned, ToTokens, TokenStreamExt};
 use syn::{
-    FnArg, Ident, ItemFn, ItemStruct, ReturnType, Visibility, __private::TokenStream2,
-    spanned::Spanned,
+    FnArg, Ident, ImplItem, ImplItemFn, ItemFn, ItemImpl, ItemStruct, Pat, ReturnType, Type,
+    Visibility, __private::TokenStream2, spanned::Spanned,
 };
 
 struct ServiceAttr {
     pub construct: String,
+    pub tags: Vec<String>,
+    pub blocking_wrappers: bool,
+    pub public: bool,
+    pub init_policy: TokenStream2,
 }
 
 impl ServiceAttr {
-    pub fn parse_attr(attr: TokenStream) -> ServiceAttr {
-        let map = parse_attr_to_map(attr);
+    pub fn parse_attr(attr: TokenStream, errors: &mut CompileErrors) -> ServiceAttr {
+        let map = AttrMap::parse_or_default(attr, errors);
         ServiceAttr {
             construct: map
-                .get("construct")
-                .or(map.get("constructor"))
-                .unwrap_or(&"".to_string())
-                .clone(),
+                .str("construct")
+                .or(map.str("constructor"))
+                .unwrap_or_default(),
+            tags: map.list("tags").unwrap_or_default(),
+            blocking_wrappers: map.has("blocking_wrappers"),
+            public: map.has("public"),
+            init_policy: init_policy(&map, errors),
         }
     }
 }
 
 #[inline]
-fn _impl_static(struct_name: &Ident, visibility: &Visibility) -> TokenStream2 {
+fn _impl_static(struct_name: &Ident, visibility: &Visibility, attribute: &ServiceAttr) -> TokenStream2 {
     let visibility_token = get_public_token(visibility);
+    let tags = &attribute.tags;
+    let init_policy = &attribute.init_policy;
     quote! {
         #[allow(non_upper_case_globals)]
-         #visibility_token static #struct_name: wildbird::Lazy<#struct_name> = wildbird::private::service_construct::<#struct_name>();
+         #visibility_token static #struct_name: wildbird::Lazy<#struct_name> = wildbird::private::service_construct::<#struct_name>()
+            .with_tags(&[#(#tags),*])#init_policy;
+        wildbird::private::auto_register!(#struct_name);
     }
 }
 
@@ -36,26 +47,22 @@ fn _impl_static(struct_name: &Ident, visibility: &Visibility) -> TokenStream2 {
 fn _impl_instance(struct_name: &Ident) -> TokenStream2 {
     quote! {
          impl #struct_name {
-            fn instance(&self) -> std::sync::Arc<#struct_name> { #struct_name.instance() }
-        }
-
-        impl wildbird::private::PrivateService<#struct_name> for #struct_name {
-            fn lazy() -> &'static wildbird::Lazy<#struct_name> { &#struct_name }
+            fn instance(&self) -> wildbird::private::Arc<#struct_name> { #struct_name.instance() }
         }
 
         impl wildbird::private::PrivateService<#struct_name> for & #struct_name {
-            fn lazy() -> &'static wildbird::Lazy<#struct_name> { &#struct_name }
+            fn inject() -> Self { #struct_name.to_ref() }
         }
 
-        impl wildbird::private::PrivateService<#struct_name> for std::sync::Arc<#struct_name> {
-            fn lazy() -> &'static wildbird::Lazy<#struct_name> { &#struct_name }
+        impl wildbird::private::PrivateService<#struct_name> for wildbird::private::Arc<#struct_name> {
+            fn inject() -> Self { #struct_name.instance() }
         }
 
         impl wildbird::private::PrivateService<#struct_name> for wildbird::Lazy<#struct_name> {
-            fn lazy() -> &'static wildbird::Lazy<#struct_name> { &#struct_name }
+            fn inject() -> Self { #struct_name.clone_lazy() }
         }
 
-        impl std::convert::From<&'static wildbird::Lazy<#struct_name>> for & #struct_name {
+        impl ::core::convert::From<&'static wildbird::Lazy<#struct_name>> for & #struct_name {
             fn from(value: &'static wildbird::Lazy<#struct_name>) -> Self {
                 &value
             }
@@ -83,24 +90,13 @@ fn _construct_function_inject(fun: &ItemFn, errors: &mut CompileErrors) -> Const
     }
 
     let new_fn_name = format_ident!("{}_inject", fn_name);
-    let mut fn_args = quote!();
     let fn_return = &fun.sig.output;
-
     for arg in args {
-        match arg {
-            FnArg::Receiver(s) => {
-                errors.add_spaned(s.span(), "'self' not allowed here".to_string());
-                fn_args.append_all(quote!(wildbird::Inject()));
-            }
-            FnArg::Typed(_) => {
-                if fn_args.is_empty() {
-                    fn_args.append_all(quote!(wildbird::Inject()))
-                } else {
-                    fn_args.append_all(quote!(, wildbird::Inject()))
-                }
-            }
+        if let FnArg::Receiver(s) = arg {
+            errors.add_spaned(s.span(), "'self' not allowed here".to_string());
         }
     }
+    let fn_args = inject_args(&fun.sig.inputs);
 
     let new_inner_fn = if is_async {
         quote!(
@@ -116,11 +112,11 @@ fn _construct_function_inject(fun: &ItemFn, errors: &mut CompileErrors) -> Const
         )
     };
 
-    return ConstructFn {
+    ConstructFn {
         fn_name: new_fn_name.to_token_stream(),
         new_inner_fn,
         is_async,
-    };
+    }
 }
 
 fn _impl_service_construct_for_function(fun: ItemFn, errors: &mut CompileErrors) -> TokenStream2 {
@@ -132,7 +128,7 @@ fn _impl_service_construct_for_function(fun: ItemFn, errors: &mut CompileErrors)
     match fun.sig.output {
         ReturnType::Default => {
             errors.add(format!("Specify function return type for: {}()", fn_name));
-            return TokenStream2::default();
+            TokenStream2::default()
         }
         ReturnType::Type(_, t) => {
             let service_type = t.to_token_stream();
@@ -157,8 +153,7 @@ fn _impl_service_construct_for_function(fun: ItemFn, errors: &mut CompileErrors)
 }
 
 #[inline]
-fn _impl_service_body(method_name: String, strict_name: &Ident, is_async: bool) -> TokenStream2 {
-    let construct_method_name = format_ident!("{}", method_name);
+fn _impl_service_body(construct_method_name: &Ident, strict_name: &Ident, is_async: bool) -> TokenStream2 {
     if is_async {
         quote! {
             { wildbird::private::block(async { #strict_name::#construct_method_name().await }) }
@@ -180,15 +175,185 @@ pub fn _impl_service(body: &TokenStream2, service_type: &TokenStream2) -> TokenS
     }
 }
 
+fn _take_construct_method(
+    service_impl: &mut ItemImpl,
+    errors: &mut CompileErrors,
+) -> Option<ImplItemFn> {
+    let mut construct: Option<ImplItemFn> = None;
+    for item in service_impl.items.iter_mut() {
+        if let ImplItem::Fn(method) = item {
+            let Some(index) = method
+                .attrs
+                .iter()
+                .position(|a| a.path().is_ident("construct"))
+            else {
+                continue;
+            };
+            let attr = method.attrs.remove(index);
+            match construct {
+                Some(_) => errors.add_spaned(
+                    attr.span(),
+                    "#[service] - Only one #[construct] method allowed".to_string(),
+                ),
+                None => construct = Some(method.clone()),
+            }
+        }
+    }
+    construct
+}
+
+fn _impl_service_construct_for_method(
+    self_ty: &Type,
+    method: &ImplItemFn,
+    errors: &mut CompileErrors,
+) -> TokenStream2 {
+    let method_name = &method.sig.ident;
+    if let Some(receiver) = method.sig.receiver() {
+        errors.add_spaned(
+            receiver.span(),
+            "#[construct] - 'self' not allowed here".to_string(),
+        );
+    }
+    if let ReturnType::Default = method.sig.output {
+        errors.add_spaned(
+            method.sig.span(),
+            format!("#[construct] - Specify return type for: {method_name}()"),
+        );
+    }
+
+    let args = inject_args(&method.sig.inputs);
+    let call = quote_spanned! { method_name.span() => #self_ty::#method_name(#args) };
+    let body = match method.sig.asyncness {
+        Some(_) => quote!({ wildbird::private::block(async { #call.await }) }),
+        None => quote!({ #call }),
+    };
+    _impl_service(&body, &self_ty.to_token_stream())
+}
+
+fn _impl_blocking_wrapper(method: &ImplItemFn) -> TokenStream2 {
+    let sig = &method.sig;
+    let method_name = &sig.ident;
+    let wrapper_name = format_ident!("{}_blocking", method_name);
+    let mut inputs = quote!();
+    let mut args = quote!();
+    for (index, input) in sig.inputs.iter().enumerate() {
+        match input {
+            FnArg::Receiver(receiver) => inputs.append_all(quote!( #receiver, )),
+            FnArg::Typed(arg) => {
+                let name = match arg.pat.as_ref() {
+                    Pat::Ident(pat) => pat.ident.clone(),
+                    _ => format_ident!("__arg{}", index),
+                };
+                let ty = &arg.ty;
+                inputs.append_all(quote!( #name: #ty, ));
+                args.append_all(quote!( #name, ));
+            }
+        }
+    }
+
+    let call = match sig.receiver() {
+        Some(_) => quote!( self.#method_name(#args) ),
+        None => quote!( Self::#method_name(#args) ),
+    };
+    let doc = format!(" Blocking [`Self::{method_name}`]");
+    let vis = &method.vis;
+    let (generics, output, where_clause) = (&sig.generics, &sig.output, &sig.generics.where_clause);
+    quote! {
+        #[doc = #doc]
+        #vis fn #wrapper_name #generics (#inputs) #output #where_clause {
+            wildbird::private::block(#call)
+        }
+    }
+}
+
+fn _impl_blocking_wrappers(service_impl: &ItemImpl) -> TokenStream2 {
+    let mut wrappers = quote!();
+    for item in service_impl.items.iter() {
+        if let ImplItem::Fn(method) = item {
+            if method.sig.asyncness.is_some() {
+                wrappers.append_all(_impl_blocking_wrapper(method));
+            }
+        }
+    }
+    let self_ty = &service_impl.self_ty;
+    let (impl_generics, _, where_clause) = service_impl.generics.split_for_impl();
+    quote! {
+        impl #impl_generics #self_ty #where_clause {
+            #wrappers
+        }
+    }
+}
+
+fn _impl_service_for_impl(
+    mut service_impl: ItemImpl,
+    attribute: &ServiceAttr,
+    errors: &mut CompileErrors,
+) -> TokenStream2 {
+    let construct = _take_construct_method(&mut service_impl, errors);
+    let wrappers = match attribute.blocking_wrappers {
+        true => _impl_blocking_wrappers(&service_impl),
+        false => quote!(),
+    };
+    if construct.is_none() && attribute.blocking_wrappers {
+        return quote!(
+            #errors
+            #service_impl
+            #[automatically_derived]
+            #wrappers
+        );
+    }
+
+    let service_ident = match service_impl.self_ty.as_ref() {
+        Type::Path(path) if service_impl.generics.params.is_empty() => {
+            path.path.segments.last().map(|s| s.ident.clone())
+        }
+        _ => None,
+    };
+
+    let (Some(service_ident), Some(construct)) = (service_ident, construct) else {
+        errors.add_spaned(
+            service_impl.self_ty.span(),
+            "#[service] - expected impl of non generic type with #[construct] method\n\tor blocking_wrappers option".to_string(),
+        );
+        return quote!( #errors #service_impl );
+    };
+
+    // Impl block doesn't carry the type's visibility, the static is private unless marked public
+    let visibility: Visibility = match attribute.public {
+        true => syn::parse_quote!(pub),
+        false => Visibility::Inherited,
+    };
+    let static_impl = _impl_static(&service_ident, &visibility, attribute);
+    let into_impl = _impl_instance(&service_ident);
+    let impl_service = _impl_service_construct_for_method(&service_impl.self_ty, &construct, errors);
+
+    quote!(
+        #errors
+        #service_impl
+        #[automatically_derived]
+        #static_impl
+        #impl_service
+        #into_impl
+        #wrappers
+    )
+}
+
 pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
     let source = TokenStream2::from(item.clone());
-    let attribute = ServiceAttr::parse_attr(attr.clone());
     let mut errors = CompileErrors::default();
+    let attribute = ServiceAttr::parse_attr(attr.clone(), &mut errors);
 
     if let Ok(construct_fn) = syn::parse::<syn::ItemFn>(item.clone()) {
+        if !attribute.tags.is_empty() {
+            errors.add_spaned(
+                construct_fn.sig.ident.span(),
+                "#[service] - tags belong to the service struct or impl block".to_string(),
+            );
+        }
         let impl_service = _impl_service_construct_for_function(construct_fn, &mut errors);
 
         let res = quote!(
+            #errors
             #source
             #[automatically_derived]
             #impl_service
@@ -197,16 +362,35 @@ pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
     };
 
     if let Ok(service_struct) = syn::parse::<ItemStruct>(item.clone()) {
+        if attribute.public {
+            errors.add_spaned(
+                service_struct.ident.span(),
+                "#[service] - public belongs to impl blocks, the struct visibility is used".to_string(),
+            );
+        }
         let strict_name = service_struct.ident;
-        let static_impl = _impl_static(&strict_name, &service_struct.vis);
+        let static_impl = _impl_static(&strict_name, &service_struct.vis, &attribute);
         let into_impl = _impl_instance(&strict_name);
         let mut impl_service = TokenStream2::default();
 
         if !attribute.construct.is_empty() {
-            let is_async = attribute.construct.contains("async");
-            let method_name = attribute.construct.replace("async", "").trim().to_string();
-            let body = _impl_service_body(method_name, &strict_name, is_async);
-            impl_service = _impl_service(&body, &strict_name.to_token_stream());
+            let (is_async, method_name) = match attribute.construct.strip_prefix("async ") {
+                Some(method_name) => (true, method_name.trim()),
+                None => (false, attribute.construct.trim()),
+            };
+            match syn::parse_str::<Ident>(method_name) {
+                Ok(method_name) => {
+                    let body = _impl_service_body(&method_name, &strict_name, is_async);
+                    impl_service = _impl_service(&body, &strict_name.to_token_stream());
+                }
+                Err(_) => errors.add_spaned(
+                    TokenStream2::from(attr).span(),
+                    format!(
+                        "#[service] - Invalid construct method: \"{}\"\n\texpected: construct = \"[async] method_name\"",
+                        attribute.construct
+                    ),
+                ),
+            }
         }
 
         let res = quote!(
@@ -220,5 +404,9 @@ pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
         return res.into();
     };
 
+    if let Ok(service_impl) = syn::parse::<ItemImpl>(item.clone()) {
+        return _impl_service_for_impl(service_impl, &attribute, &mut errors).into();
+    };
+
     item
 }
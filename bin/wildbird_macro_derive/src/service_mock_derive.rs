@@ -0,0 +1,269 @@
+use crate::_utils::*;
+use proc_macro::TokenStream;
+use quote::{format_ident, quote, ToTokens, TokenStreamExt};
+use syn::{
+    spanned::Spanned, FnArg, Ident, ImplItem, ItemImpl, ItemTrait, ReturnType, Signature,
+    TraitItem, Type, Visibility, __private::TokenStream2,
+};
+
+struct MockTarget {
+    trait_path: TokenStream2,
+    trait_name: Ident,
+    visibility: TokenStream2,
+    methods: Vec<Signature>,
+}
+
+fn _mock_target(item: &TokenStream, errors: &mut CompileErrors) -> Option<MockTarget> {
+    if let Ok(service_trait) = syn::parse::<ItemTrait>(item.clone()) {
+        if !service_trait.generics.params.is_empty() {
+            errors.add_spaned(
+                service_trait.generics.span(),
+                "#[service_mock] - generic traits are not supported".to_string(),
+            );
+        }
+        let methods = service_trait
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                TraitItem::Fn(method) => Some(method.sig.clone()),
+                _ => None,
+            })
+            .collect();
+        return Some(MockTarget {
+            trait_path: service_trait.ident.to_token_stream(),
+            trait_name: service_trait.ident,
+            visibility: service_trait.vis.to_token_stream(),
+            methods,
+        });
+    }
+
+    if let Ok(service_impl) = syn::parse::<ItemImpl>(item.clone()) {
+        let Some((_, trait_path, _)) = &service_impl.trait_ else {
+            errors.add_spaned(
+                service_impl.self_ty.span(),
+                "#[service_mock] - expected service trait or trait impl block".to_string(),
+            );
+            return None;
+        };
+        let trait_name = trait_path.segments.last()?.ident.clone();
+        let methods = service_impl
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                ImplItem::Fn(method) => Some(method.sig.clone()),
+                _ => None,
+            })
+            .collect();
+        return Some(MockTarget {
+            trait_path: trait_path.to_token_stream(),
+            trait_name,
+            visibility: Visibility::Inherited.to_token_stream(),
+            methods,
+        });
+    }
+
+    errors.add("#[service_mock] - expected service trait or trait impl block".to_string());
+    None
+}
+
+fn _validate_method(sig: &Signature, errors: &mut CompileErrors) -> bool {
+    if sig.receiver().is_none() {
+        errors.add_spaned(
+            sig.span(),
+            format!("#[service_mock] - {}(): method without 'self' is not supported", sig.ident),
+        );
+        return false;
+    }
+    if !sig.generics.params.is_empty() {
+        errors.add_spaned(
+            sig.generics.span(),
+            format!("#[service_mock] - {}(): generic methods are not supported", sig.ident),
+        );
+        return false;
+    }
+    true
+}
+
+fn _arg_types(sig: &Signature) -> Vec<&Type> {
+    sig.inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            FnArg::Typed(arg) => Some(arg.ty.as_ref()),
+            FnArg::Receiver(_) => None,
+        })
+        .collect()
+}
+
+fn _impl_expectation(
+    sig: &Signature,
+    mock_name: &Ident,
+    target: &MockTarget,
+) -> (Ident, TokenStream2, TokenStream2) {
+    let method_name = &sig.ident;
+    let expectation_name = format_ident!("{}_{}", mock_name, method_name);
+    let visibility = &target.visibility;
+    let arg_types = _arg_types(sig);
+    let output = match &sig.output {
+        ReturnType::Default => quote!(()),
+        ReturnType::Type(_, t) => t.to_token_stream(),
+    };
+    let returning_type = quote!( dyn FnMut(#(#arg_types),*) -> #output + Send );
+    let matcher_type = quote!( dyn Fn(#(&#arg_types),*) -> bool + Send );
+
+    let expectation = quote! {
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        #visibility struct #expectation_name(wildbird::testing::Expectation<#returning_type, #matcher_type>);
+
+        impl #expectation_name {
+            /// Value returned by the mocked method
+            pub fn returning(&self, returning: impl FnMut(#(#arg_types),*) -> #output + Send + 'static) -> &Self {
+                self.0.set_returning(Box::new(returning));
+                self
+            }
+
+            /// Arguments matcher, panics on call when not matched
+            pub fn with(&self, matcher: impl Fn(#(&#arg_types),*) -> bool + Send + 'static) -> &Self {
+                self.0.set_matcher(Box::new(matcher));
+                self
+            }
+
+            /// Expected number of calls, verified by `checkpoint()`
+            pub fn times(&self, times: usize) -> &Self {
+                self.0.set_times(times);
+                self
+            }
+
+            pub fn calls(&self) -> usize {
+                self.0.calls()
+            }
+        }
+    };
+
+    let args: Vec<Ident> = (0..arg_types.len())
+        .map(|index| format_ident!("__arg{}", index))
+        .collect();
+    let receiver = sig.receiver().map(|r| r.to_token_stream());
+    let asyncness = &sig.asyncness;
+    let fn_output = &sig.output;
+    let mocked_method = quote! {
+        #asyncness fn #method_name(#receiver, #(#args: #arg_types),*) #fn_output {
+            self.#method_name.0.check(|matcher| matcher(#(&#args),*));
+            self.#method_name.0.returning(|returning| returning(#(#args),*))
+        }
+    };
+
+    (expectation_name, expectation, mocked_method)
+}
+
+fn _impl_mock(target: &MockTarget, errors: &mut CompileErrors) -> TokenStream2 {
+    let trait_path = &target.trait_path;
+    let trait_name = &target.trait_name;
+    let visibility = &target.visibility;
+    let mock_name = format_ident!("Mock{}", trait_name);
+
+    let mut expectations = quote!();
+    let mut mocked_methods = quote!();
+    let mut fields = quote!();
+    let mut defaults = quote!();
+    let mut accessors = quote!();
+    let mut checkpoints = quote!();
+    let mut is_dyn_compatible = true;
+
+    for sig in target.methods.iter() {
+        if !_validate_method(sig, errors) {
+            continue;
+        }
+        is_dyn_compatible &= sig.asyncness.is_none();
+        let method_name = &sig.ident;
+        let expect_name = format_ident!("expect_{}", method_name);
+        let full_name = format!("{}::{}", trait_name, method_name);
+        let (expectation_name, expectation, mocked_method) =
+            _impl_expectation(sig, &mock_name, target);
+
+        expectations.append_all(expectation);
+        mocked_methods.append_all(mocked_method);
+        fields.append_all(quote!( #method_name: std::sync::Arc<#expectation_name>, ));
+        defaults.append_all(quote! {
+            #method_name: std::sync::Arc::new(#expectation_name(wildbird::testing::Expectation::new(#full_name))),
+        });
+        accessors.append_all(quote! {
+            pub fn #expect_name(&self) -> &#expectation_name {
+                &self.#method_name
+            }
+        });
+        checkpoints.append_all(quote!( self.#method_name.0.checkpoint(); ));
+    }
+
+    let mut conversions = quote!();
+    if is_dyn_compatible {
+        for (pointer, target_type) in [
+            (quote!(Box), quote!(dyn #trait_path)),
+            (quote!(Box), quote!(dyn #trait_path + Send + Sync)),
+            (quote!(std::sync::Arc), quote!(dyn #trait_path)),
+            (quote!(std::sync::Arc), quote!(dyn #trait_path + Send + Sync)),
+        ] {
+            conversions.append_all(quote! {
+                impl From<#mock_name> for #pointer<#target_type> {
+                    fn from(mock: #mock_name) -> Self {
+                        #pointer::new(mock)
+                    }
+                }
+            });
+        }
+    }
+
+    quote! {
+        wildbird::private::testing_only! {
+            #expectations
+
+            /// Generated by `#[service_mock]`, clones share expectations
+            #[derive(Clone)]
+            #visibility struct #mock_name {
+                #fields
+            }
+
+            impl Default for #mock_name {
+                fn default() -> Self {
+                    Self { #defaults }
+                }
+            }
+
+            impl #mock_name {
+                pub fn new() -> Self {
+                    Self::default()
+                }
+
+                #accessors
+
+                /// Panics when any expected number of calls doesn't match
+                pub fn checkpoint(&self) {
+                    #checkpoints
+                }
+            }
+
+            impl #trait_path for #mock_name {
+                #mocked_methods
+            }
+
+            #conversions
+        }
+    }
+}
+
+pub fn main(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut errors = CompileErrors::default();
+    let source = TokenStream2::from(item.clone());
+    let Some(target) = _mock_target(&item, &mut errors) else {
+        return quote!( #errors #source ).into();
+    };
+
+    let mock = _impl_mock(&target, &mut errors);
+    let res = quote! {
+        #errors
+        #source
+        #[automatically_derived]
+        #mock
+    };
+    res.into()
+}
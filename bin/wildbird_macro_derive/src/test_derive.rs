@@ -0,0 +1,130 @@
+use crate::_utils::*;
+use proc_macro::TokenStream;
+use quote::{format_ident, quote, TokenStreamExt};
+use syn::parse::{Parse, ParseStream, Parser};
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{
+    spanned::Spanned, Expr, ExprLit, FnArg, ItemFn, Lit, Meta, MetaNameValue, __private::TokenStream2,
+};
+
+struct TestAttr {
+    multi_thread: bool,
+    worker_threads: usize,
+    overrides: Vec<MetaNameValue>,
+}
+
+impl Parse for TestAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attr = TestAttr {
+            multi_thread: true,
+            worker_threads: 2,
+            overrides: vec![],
+        };
+        for meta in Punctuated::<Meta, Comma>::parse_terminated(input)? {
+            match meta {
+                Meta::List(list) if list.path.is_ident("overrides") => {
+                    let overrides = Punctuated::<MetaNameValue, Comma>::parse_terminated.parse2(list.tokens)?;
+                    attr.overrides.extend(overrides);
+                }
+                Meta::NameValue(MetaNameValue {
+                    path,
+                    value: Expr::Lit(ExprLit { lit, .. }),
+                    ..
+                }) => match lit {
+                    Lit::Str(flavor) if path.is_ident("flavor") => match flavor.value().as_str() {
+                        "multi_thread" => attr.multi_thread = true,
+                        "current_thread" => attr.multi_thread = false,
+                        _ => {
+                            return Err(syn::Error::new(
+                                flavor.span(),
+                                "expected: flavor = \"multi_thread\" | \"current_thread\"",
+                            ))
+                        }
+                    },
+                    Lit::Int(threads) if path.is_ident("worker_threads") => {
+                        attr.worker_threads = threads.base10_parse()?;
+                    }
+                    lit => return Err(syn::Error::new(lit.span(), "unexpected attribute")),
+                },
+                meta => {
+                    return Err(syn::Error::new(
+                        meta.span(),
+                        "expected: flavor, worker_threads or overrides(STATIC = value, ..)",
+                    ))
+                }
+            }
+        }
+        Ok(attr)
+    }
+}
+
+fn _impl_test(test_fn: ItemFn, attr: &TestAttr, errors: &mut CompileErrors) -> TokenStream2 {
+    let mut overrides = quote!();
+    for (index, entry) in attr.overrides.iter().enumerate() {
+        let guard = format_ident!("__override_{}", index);
+        let (lazy, value) = (&entry.path, &entry.value);
+        overrides.append_all(quote!( let #guard = #lazy.set_override(#value); ));
+    }
+
+    let mut fixtures = quote!();
+    for arg in test_fn.sig.inputs.iter() {
+        match arg {
+            FnArg::Typed(arg) => {
+                let (pat, ty) = (&arg.pat, &arg.ty);
+                fixtures.append_all(quote!( let #pat: #ty = wildbird::private::inject(); ));
+            }
+            FnArg::Receiver(receiver) => {
+                errors.add_spaned(receiver.span(), "#[wildbird::testing::test] - 'self' not allowed here".to_string())
+            }
+        }
+    }
+
+    let attrs = &test_fn.attrs;
+    let vis = &test_fn.vis;
+    let name = &test_fn.sig.ident;
+    let output = &test_fn.sig.output;
+    let block = &test_fn.block;
+    let body = match test_fn.sig.asyncness {
+        Some(_) => {
+            let (multi_thread, worker_threads) = (attr.multi_thread, attr.worker_threads);
+            quote! {
+                wildbird::private::block_test(async move { #fixtures #block }, #multi_thread, #worker_threads)
+            }
+        }
+        None => quote!( #fixtures #block ),
+    };
+
+    quote! {
+        #[test]
+        #(#attrs)*
+        #vis fn #name() #output {
+            let __scope = wildbird::InjectScope::isolate();
+            #overrides
+            #body
+        }
+    }
+}
+
+pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let Ok(test_fn) = syn::parse::<ItemFn>(item) else {
+        let mut errors = CompileErrors::default();
+        errors.add("#[wildbird::testing::test] - expected fn".to_string());
+        return errors.into();
+    };
+
+    let mut errors = CompileErrors::default();
+    let attr = match syn::parse::<TestAttr>(attr) {
+        Ok(attr) => attr,
+        Err(e) => {
+            errors.add_spaned(e.span(), format!("#[wildbird::testing::test] - {e}"));
+            return errors.into();
+        }
+    };
+    let test = _impl_test(test_fn, &attr, &mut errors);
+    let res = quote! {
+        #errors
+        #test
+    };
+    res.into()
+}
@@ -1,33 +1,263 @@
 use crate::_utils::*;
 use proc_macro::TokenStream;
-use quote::{format_ident, quote, ToTokens};
-use std::str::FromStr;
-use syn::{spanned::Spanned, FnArg, ItemFn, PatType, ReturnType, __private::TokenStream2};
+use quote::{format_ident, quote, ToTokens, TokenStreamExt};
+use syn::{spanned::Spanned, FnArg, GenericArgument, ItemFn, PatType, PathArguments, ReturnType, Type, __private::TokenStream2};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MutableLock {
+    Sync,
+    Async,
+}
 
 pub struct VarAttr {
     pub name: Option<String>,
+    pub init_policy: TokenStream2,
+    pub no_arc: bool,
+    pub thread_local: bool,
+    /// `(ttl, refresh_ahead)` in milliseconds
+    pub ttl: Option<(u64, Option<u64>)>,
+    pub watch: bool,
+    /// Callback var timeout in milliseconds
+    pub timeout: Option<u64>,
+    /// `(key, version)` of disk cached value
+    pub persist: Option<(String, u32)>,
+    /// Wrapped in `Secret<T>`, redacted in `snapshot_vars()`
+    pub secret: bool,
+    /// Docker style secret file, read before calling the function
+    pub secret_file: Option<TokenStream2>,
+    pub mutable: Option<MutableLock>,
+}
+
+fn duration_attr(map: &AttrMap, key: &str, errors: &mut CompileErrors) -> Option<u64> {
+    let value = map.str(key)?;
+    let millis = duration_millis(&value);
+    if millis.is_none() {
+        errors.add(format!(
+            "#[var] - Invalid {key}: \"{value}\"\n\texpected: {key} = \"500ms\" | \"30s\" | \"5m\" | \"1h\" | \"1d\""
+        ));
+    }
+    millis
 }
 
 impl VarAttr {
-    pub fn parse_attr(attr: TokenStream) -> VarAttr {
-        let map = parse_attr_to_map(attr);
+    pub fn parse_attr(attr: TokenStream, errors: &mut CompileErrors) -> VarAttr {
+        let map = AttrMap::parse_or_default(attr, errors);
+        let no_arc = map.has("no_arc");
+        let thread_local = map.has("thread_local");
+        if no_arc && map.has("init_policy") {
+            errors.add("#[var] - init_policy is not supported with no_arc".to_string());
+        }
+        if thread_local && (no_arc || map.has("init_policy")) {
+            errors.add("#[var] - thread_local can't be combined with no_arc or init_policy".to_string());
+        }
+        let ttl = duration_attr(&map, "ttl", errors);
+        let refresh_ahead = duration_attr(&map, "refresh_ahead", errors);
+        if map.has("refresh_ahead") && !map.has("ttl") {
+            errors.add("#[var] - refresh_ahead requires ttl".to_string());
+        }
+        if let (Some(ttl), Some(ahead)) = (ttl, refresh_ahead) {
+            if ahead >= ttl {
+                errors.add("#[var] - refresh_ahead must be shorter than ttl".to_string());
+            }
+        }
+        if map.has("ttl") && (no_arc || thread_local || map.has("init_policy")) {
+            errors.add("#[var] - ttl can't be combined with no_arc, thread_local or init_policy".to_string());
+        }
+        let watch = map.has("watch");
+        if watch && (no_arc || thread_local || map.has("ttl") || map.has("init_policy")) {
+            errors.add("#[var] - watch can't be combined with no_arc, thread_local, ttl or init_policy".to_string());
+        }
+        let secret_file = map.expr("secret_file");
+        let secret = map.has("secret") || secret_file.is_some();
+        if map.str("secret_file").as_deref() == Some("") {
+            errors.add("#[var] - Invalid secret_file\n\texpected: secret_file = \"/run/secrets/name\" | concat!(..)".to_string());
+        }
+        if secret && (watch || map.has("persist")) {
+            errors.add("#[var] - secret can't be combined with watch or persist".to_string());
+        }
+        let mutable = mutable_attr(&map, errors);
+        if mutable.is_some() && (no_arc || thread_local || watch || map.has("ttl") || map.has("init_policy") || map.has("persist")) {
+            errors.add("#[var] - mutable can't be combined with no_arc, thread_local, ttl, watch, init_policy or persist".to_string());
+        }
         VarAttr {
-            name: map.get("name").cloned(),
+            name: map.str("name"),
+            init_policy: init_policy(&map, errors),
+            no_arc,
+            thread_local,
+            ttl: ttl.map(|ttl| (ttl, refresh_ahead)),
+            watch,
+            timeout: duration_attr(&map, "timeout", errors),
+            persist: persist_attr(&map, errors),
+            secret,
+            secret_file,
+            mutable,
+        }
+    }
+}
+
+fn mutable_attr(map: &AttrMap, errors: &mut CompileErrors) -> Option<MutableLock> {
+    if !map.has("mutable") {
+        return None;
+    }
+    match map.str("mutable").as_deref() {
+        None | Some("sync") => Some(MutableLock::Sync),
+        Some("async") => Some(MutableLock::Async),
+        Some(lock) => {
+            errors.add(format!("#[var] - Invalid mutable: \"{lock}\"\n\texpected: mutable | mutable = \"sync\" | \"async\""));
+            None
         }
     }
 }
 
+fn persist_attr(map: &AttrMap, errors: &mut CompileErrors) -> Option<(String, u32)> {
+    let version = match map.str("version") {
+        None => 1,
+        Some(version) => version.parse().unwrap_or_else(|_| {
+            errors.add(format!("#[var] - Invalid version: {version}\n\texpected: version = 3"));
+            1
+        }),
+    };
+    if !map.has("persist") {
+        if map.has("version") {
+            errors.add("#[var] - version requires persist".to_string());
+        }
+        return None;
+    }
+    if map.has("ttl") || map.has("watch") || map.has("thread_local") {
+        errors.add("#[var] - persist can't be combined with ttl, watch or thread_local".to_string());
+    }
+    match map.str("persist") {
+        Some(key) if !_relative_key(&key) => {
+            errors.add(format!("#[var] - Invalid persist key: {key}\n\texpected: relative path without \"..\", e.g. persist = \"dictionary/en\""));
+            None
+        }
+        Some(key) if !key.is_empty() => Some((key, version)),
+        _ => {
+            errors.add("#[var] - Invalid persist\n\texpected: persist = \"path/or/key\"".to_string());
+            None
+        }
+    }
+}
+
+// Cache file stays in the cache dir
+fn _relative_key(key: &str) -> bool {
+    use std::path::{Component, Path};
+    Path::new(key).components().all(|component| matches!(component, Component::Normal(_)))
+}
+
 #[inline]
 fn impl_static(
     function_name: &TokenStream2,
     const_name: &str,
     return_type: &TokenStream2,
     visibility_token: &TokenStream2,
+    attribute: &VarAttr,
+) -> TokenStream2 {
+    if attribute.secret {
+        return impl_secret_static(function_name, const_name, return_type, visibility_token, attribute);
+    }
+    let Some((key, version)) = &attribute.persist else {
+        return impl_lazy_static(function_name, const_name, return_type, visibility_token, attribute);
+    };
+    let persisted = format_ident!("_{}_persisted", const_name.to_lowercase()).to_token_stream();
+    let static_impl = impl_lazy_static(&persisted, const_name, return_type, visibility_token, attribute);
+    quote! {
+        fn #persisted() -> #return_type {
+            wildbird::private::persisted!(#key, #version, #function_name)
+        }
+        #static_impl
+    }
+}
+
+#[inline]
+fn impl_secret_static(
+    function_name: &TokenStream2,
+    const_name: &str,
+    return_type: &TokenStream2,
+    visibility_token: &TokenStream2,
+    attribute: &VarAttr,
+) -> TokenStream2 {
+    let secret = format_ident!("_{}_secret", const_name.to_lowercase()).to_token_stream();
+    let secret_type = quote!( wildbird::Secret<#return_type> );
+    let static_impl = impl_lazy_static(&secret, const_name, &secret_type, visibility_token, attribute);
+    let value = match &attribute.secret_file {
+        Some(path) => quote!( wildbird::private::secret_file!(#path, #function_name) ),
+        None => quote!( #function_name() ),
+    };
+    quote! {
+        fn #secret() -> #secret_type {
+            wildbird::private::secret!(#return_type, #value)
+        }
+        #static_impl
+    }
+}
+
+#[inline]
+fn impl_lazy_static(
+    function_name: &TokenStream2,
+    const_name: &str,
+    return_type: &TokenStream2,
+    visibility_token: &TokenStream2,
+    attribute: &VarAttr,
 ) -> TokenStream2 {
     let static_name = format_ident!("{const_name}");
+    let init_policy = &attribute.init_policy;
+    let secret = attribute.secret;
+    if let Some((ttl, refresh_ahead)) = attribute.ttl {
+        let refresh_ahead = refresh_ahead.map(|ahead| quote!( .with_refresh_ahead(::core::time::Duration::from_millis(#ahead)) ));
+        return quote! {
+            #[allow(non_upper_case_globals)]
+            #visibility_token static #static_name: wildbird::LazyTtl<#return_type> =
+                wildbird::private::ttl_construct(#function_name, ::core::time::Duration::from_millis(#ttl))#refresh_ahead;
+            wildbird::private::register_var!(#static_name: wildbird::LazyTtl<#return_type>, #const_name, #secret);
+        };
+    }
+    if attribute.thread_local {
+        return quote! {
+            #[allow(non_upper_case_globals)]
+            #visibility_token static #static_name: wildbird::LazyLocal<#return_type> = {
+                ::std::thread_local! {
+                    static SLOT: wildbird::private::LocalSlot<#return_type> = const { ::core::cell::RefCell::new(::core::option::Option::None) };
+                }
+                wildbird::private::local_construct(&SLOT, #function_name)
+            };
+            wildbird::private::auto_register!(#static_name);
+        };
+    }
+    match attribute.mutable {
+        Some(MutableLock::Sync) => {
+            return quote! {
+                #[allow(non_upper_case_globals)]
+                #visibility_token static #static_name: wildbird::LazyMut<#return_type> = wildbird::private::mut_construct(#function_name);
+                wildbird::private::register_var!(#static_name: wildbird::LazyMut<#return_type>, #const_name, #secret);
+            };
+        }
+        Some(MutableLock::Async) => {
+            return quote! {
+                wildbird::private::mut_async!(#visibility_token static #static_name: #return_type = #function_name, #const_name, #secret);
+            };
+        }
+        None => {}
+    }
+    if attribute.no_arc {
+        return quote! {
+            #[allow(non_upper_case_globals)]
+            #visibility_token static #static_name: wildbird::LazyValue<#return_type> = wildbird::private::value_construct(#function_name);
+            wildbird::private::register_var!(#static_name: wildbird::LazyValue<#return_type>, #const_name, #secret);
+
+            // Injected as `&'static T`, the marker keeps the binding within the orphan rules
+            const _: () = {
+                struct Value;
+                impl wildbird::private::PrivateService<Value> for &'static #return_type {
+                    fn inject() -> Self { #static_name.to_ref() }
+                }
+            };
+        };
+    }
     quote! {
         #[allow(non_upper_case_globals)]
-        #visibility_token static #static_name: wildbird::Lazy<#return_type> = wildbird::private::lazy_construct(#function_name);
+        #visibility_token static #static_name: wildbird::Lazy<#return_type> = wildbird::private::lazy_construct(#function_name)#init_policy;
+        wildbird::private::register_var!(#static_name: wildbird::Lazy<#return_type>, #const_name, #secret);
     }
 }
 
@@ -38,115 +268,176 @@ fn _get_var_fn_output_type(fun: &ItemFn) -> TokenStream2 {
     }
 }
 
-fn _impl_var_static(
-    fun: &ItemFn,
-    first_arg: Option<&&PatType>,
-    attribute: VarAttr,
-) -> TokenStream2 {
+/// `Callback<T>` parameter, matched on the last path segment (`wildbird::Callback<T>` too)
+fn is_callback(arg: &PatType) -> bool {
+    match arg.ty.as_ref() {
+        Type::Path(path) => path.path.segments.last().is_some_and(|segment| segment.ident == "Callback"),
+        _ => false,
+    }
+}
+
+/// Call arguments: `callback` for the `Callback<T>` parameter, injected services for the rest
+fn _var_call_args(fields: &[&PatType], bind: bool) -> (TokenStream2, TokenStream2) {
+    let mut bindings = quote!();
+    let mut args = quote!();
+    for (index, arg) in fields.iter().enumerate() {
+        let value = if is_callback(arg) {
+            quote!(__callback)
+        } else if bind {
+            let name = format_ident!("__arg{index}");
+            let ty = &arg.ty;
+            bindings.append_all(quote!( let #name: #ty = wildbird::private::inject(); ));
+            name.to_token_stream()
+        } else {
+            quote!(wildbird::private::inject())
+        };
+        args.append_all(quote!( #value, ));
+    }
+    (bindings, args)
+}
+
+fn _impl_var_static(fun: &ItemFn, fields: &[&PatType], attribute: &VarAttr) -> TokenStream2 {
     let is_async = fun.sig.asyncness.is_some();
     let function_name = fun.sig.ident.to_token_stream();
     let visibility_token = get_public_token(&fun.vis);
-    let mut return_type = _get_var_fn_output_type(fun);
+    let return_type = _get_var_fn_output_type(fun);
     let static_name = attribute
         .name
+        .clone()
         .unwrap_or(function_name.to_string().to_uppercase());
 
-    if !is_async {
+    if !is_async && fields.is_empty() {
         return impl_static(
             &function_name,
             &static_name,
             &return_type,
             &visibility_token,
+            attribute,
         );
     }
 
     let init_function_name = format_ident!("_{}_init", function_name.to_string()).to_token_stream();
-    let mut static_impl = impl_static(
-        &init_function_name,
-        &static_name,
-        &return_type,
-        &visibility_token,
-    );
-    let mut body = quote!( wildbird::private::block_fn(#function_name) );
-    if let Some(_callback_arg) = first_arg {
-        return_type = unwrap_callback_type(_callback_arg.ty.to_token_stream());
-        static_impl = impl_static(
+    let Some(callback_arg) = fields.iter().find(|arg| is_callback(arg)) else {
+        let (_, args) = _var_call_args(fields, false);
+        let body = match is_async {
+            true => quote!( wildbird::private::block(#function_name(#args)) ),
+            false => quote!( #function_name(#args) ),
+        };
+        let static_impl = impl_static(
             &init_function_name,
             &static_name,
             &return_type,
             &visibility_token,
+            attribute,
         );
-        body = quote!( wildbird::private::block_callback(#function_name) );
-    }
+        return quote!(
+            fn #init_function_name() -> #return_type { #body }
+            #static_impl
+        );
+    };
 
+    // Injected before the publisher thread starts, on the thread accessing the var
+    let (bindings, args) = _var_call_args(fields, true);
+    let Some(return_type) = unwrap_callback_type(&callback_arg.ty) else {
+        // Reported by `_var_validate`
+        return quote!();
+    };
+    let return_type = return_type.to_token_stream();
+    if attribute.watch {
+        let const_name = static_name.clone();
+        let static_name = format_ident!("{static_name}");
+        return quote!(
+            fn #init_function_name() -> wildbird::private::WatchStart<#return_type> {
+                #bindings
+                ::std::boxed::Box::new(move |__callback| wildbird::private::block(#function_name(#args)))
+            }
+            #[allow(non_upper_case_globals)]
+            #visibility_token static #static_name: wildbird::LazyWatch<#return_type> = wildbird::private::watch_construct(#init_function_name);
+            wildbird::private::register_var!(#static_name: wildbird::LazyWatch<#return_type>, #const_name, false);
+        );
+    }
+    let static_impl = impl_static(
+        &init_function_name,
+        &static_name,
+        &return_type,
+        &visibility_token,
+        attribute,
+    );
+    let timeout = match attribute.timeout {
+        Some(millis) => quote!( ::core::option::Option::Some(::core::time::Duration::from_millis(#millis)) ),
+        None => quote!( ::core::option::Option::None ),
+    };
     quote!(
-        fn #init_function_name() -> #return_type { #body }
+        fn #init_function_name() -> #return_type {
+            static __PUBLISHER: wildbird::private::Publisher<#return_type> = wildbird::private::Publisher::new();
+            #bindings
+            wildbird::private::block_callback(&__PUBLISHER, move |__callback| #function_name(#args), #timeout)
+        }
         #static_impl
     )
 }
 
-fn unwrap_callback_type(callback_arg_type: TokenStream2) -> TokenStream2 {
-    let str = callback_arg_type.to_string().trim().replace(' ', "");
-    let from = str.find("Callback<").map(|v| v + 9).unwrap_or(0);
-    let to = str.rfind('>').unwrap_or(str.len());
-    TokenStream2::from_str(&str[from..to]).expect("Valid type")
+/// `T` of `Callback<T>`
+fn unwrap_callback_type(callback_arg_type: &Type) -> Option<&Type> {
+    let Type::Path(path) = callback_arg_type else {
+        return None;
+    };
+    let PathArguments::AngleBracketed(arguments) = &path.path.segments.last()?.arguments else {
+        return None;
+    };
+    match arguments.args.first()? {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    }
 }
 
-fn _var_validate(lazy_fn: &ItemFn, fields: &Vec<&PatType>, errors: &mut CompileErrors) {
-    if let Some(first_arg) = fields.get(0) {
-        
-        let first_arg_type = first_arg
-            .ty
-            .to_token_stream()
-            .to_string()
-            .trim()
-            .replace(' ', "");
-        let first_arg_name = first_arg.pat.to_token_stream().to_string();
-
-        if fields.len() == 1 && !first_arg_type.contains("Callback<") {
-            errors.add_spaned(
-                fields.as_slice()[0].ty.span(),
-                "#[var] - Invalid \"callback\" first arg type: \n\texpected: Callback<T>"
-                    .to_string(),
-            );
-        }
-
-        if lazy_fn.sig.asyncness.is_none() {
-            errors.add_spaned(
-                lazy_fn.sig.span(),
-                "#[var] - Callback function must by async".to_string(),
-            );
-        }
-
-        let call_exist = format!("{first_arg_name}.call(");
-        let body = lazy_fn
-            .block
-            .to_token_stream()
-            .to_string()
-            .trim()
-            .replace(' ', "");
-        if !body.contains(&call_exist) {
-            errors.add_spaned(
-                lazy_fn.block.span(),
-                format!("#[var] - {first_arg_name}.call(T): method must be called"),
-            );
-        }
-
-        if fields.len() > 1 {
-            errors.add_spaned(
-                lazy_fn.sig.inputs.span(),
-                "#[var] - Invalid number of arguments\n\t zero or one (callback) allowed".to_string(),
-            );
-        }
+fn _var_validate(lazy_fn: &ItemFn, fields: &[&PatType], errors: &mut CompileErrors) {
+    let callbacks: Vec<&&PatType> = fields.iter().filter(|arg| is_callback(arg)).collect();
+    if callbacks.len() > 1 {
+        errors.add_spaned(
+            lazy_fn.sig.inputs.span(),
+            "#[var] - Invalid number of arguments\n\t at most one Callback<T> allowed".to_string(),
+        );
+    }
 
-    } else {
+    let Some(callback_arg) = callbacks.first() else {
         if let ReturnType::Default = lazy_fn.sig.output {
             errors.add_spaned(
                 lazy_fn.sig.span(),
                 "Specify function return type".to_string(),
             );
         }
+        return;
     };
+
+    if lazy_fn.sig.asyncness.is_none() {
+        errors.add_spaned(
+            lazy_fn.sig.span(),
+            "#[var] - Callback function must by async".to_string(),
+        );
+    }
+
+    if unwrap_callback_type(&callback_arg.ty).is_none() {
+        errors.add_spaned(
+            callback_arg.ty.span(),
+            "#[var] - Invalid Callback type\n\texpected: Callback<T>".to_string(),
+        );
+    }
+
+    let callback_name = callback_arg.pat.to_token_stream().to_string();
+    let call_exist = format!("{callback_name}.call(");
+    let body = lazy_fn
+        .block
+        .to_token_stream()
+        .to_string()
+        .trim()
+        .replace(' ', "");
+    if !body.contains(&call_exist) {
+        errors.add_spaned(
+            lazy_fn.block.span(),
+            format!("#[var] - {callback_name}.call(T): method must be called"),
+        );
+    }
 }
 
 pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -161,9 +452,23 @@ pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
             return errors.into();
         }
 
-        let first_arg = fields.get(0);
-        let attribute = VarAttr::parse_attr(attr.clone());
-        let static_impl = _impl_var_static(&lazy_fn, first_arg, attribute);
+        let has_callback = fields.iter().any(|arg| is_callback(arg));
+        let attribute = VarAttr::parse_attr(attr.clone(), &mut errors);
+        if attribute.timeout.is_some() && (!has_callback || attribute.watch) {
+            errors.add_spaned(
+                lazy_fn.sig.span(),
+                "#[var] - timeout requires async fn with callback: Callback<T> argument, without watch".to_string(),
+            );
+            return errors.into();
+        }
+        if attribute.watch && !has_callback {
+            errors.add_spaned(
+                lazy_fn.sig.span(),
+                "#[var] - watch requires async fn with callback: Callback<T> argument".to_string(),
+            );
+            return errors.into();
+        }
+        let static_impl = _impl_var_static(&lazy_fn, &fields, &attribute);
 
         let res = quote! {
             #errors
@@ -0,0 +1,53 @@
+use crate::_utils::*;
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{spanned::Spanned, ItemFn, ReturnType, __private::TokenStream2};
+
+fn _impl_verify_test(test_fn: &ItemFn, errors: &mut CompileErrors) -> TokenStream2 {
+    let sig = &test_fn.sig;
+    if sig.asyncness.is_some() || !sig.inputs.is_empty() {
+        errors.add_spaned(
+            sig.span(),
+            "#[verify_test] - expected fn without arguments".to_string(),
+        );
+    }
+
+    let attrs = &test_fn.attrs;
+    let vis = &test_fn.vis;
+    let name = &sig.ident;
+    let block = &test_fn.block;
+    let run = match &sig.output {
+        ReturnType::Default => quote! {
+            (|| #block)();
+            wildbird::verify().assert_ok();
+        },
+        ReturnType::Type(_, output) => quote! {
+            let verify: #output = (|| #block)();
+            wildbird::Verify::from(verify).run().assert_ok();
+        },
+    };
+
+    quote! {
+        #[test]
+        #(#attrs)*
+        #vis fn #name() {
+            #run
+        }
+    }
+}
+
+pub fn main(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let Ok(test_fn) = syn::parse::<ItemFn>(item) else {
+        let mut errors = CompileErrors::default();
+        errors.add("#[verify_test] - expected fn".to_string());
+        return errors.into();
+    };
+
+    let mut errors = CompileErrors::default();
+    let test = _impl_verify_test(&test_fn, &mut errors);
+    let res = quote! {
+        #errors
+        #test
+    };
+    res.into()
+}
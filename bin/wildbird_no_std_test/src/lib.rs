@@ -0,0 +1,97 @@
+#![no_std]
+#![allow(dead_code, non_snake_case)]
+
+//! Checks `#[service]` and `#[var]` in `no_std` crate
+//! use:  cargo test -p wildbird_no_std_test
+
+extern crate alloc;
+#[cfg(test)]
+extern crate std;
+
+use alloc::string::{String, ToString};
+use wildbird::prelude::*;
+
+#[var]
+fn board_name() -> String {
+    "stm32".to_string()
+}
+
+#[service(construct = "init")]
+pub struct Clock {
+    ticks: u32,
+}
+
+impl Clock {
+    fn init() -> Clock {
+        Clock { ticks: 1000 }
+    }
+}
+
+#[service]
+pub struct Sensor {
+    clock: Arc<Clock>,
+}
+
+#[service(construct)]
+fn sensor_init(clock: Arc<Clock>) -> Sensor {
+    Sensor { clock }
+}
+
+impl Sensor {
+    pub fn read(&self) -> u32 {
+        self.clock.ticks + 1
+    }
+}
+
+#[var]
+fn recursive() -> u32 {
+    *RECURSIVE + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wildbird::LazyState;
+
+    #[service(construct = "init")]
+    pub struct Calibration {
+        offset: u32,
+    }
+
+    impl Calibration {
+        fn init() -> Calibration {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            Calibration { offset: 7 }
+        }
+    }
+
+    // Thread stands in for a core
+    fn context_id() -> usize {
+        std::thread_local!(static CONTEXT: u8 = const { 0 });
+        CONTEXT.with(|context| context as *const u8 as usize)
+    }
+
+    #[test]
+    fn should_inject_services() {
+        assert_eq!(Sensor.read(), 1001);
+        assert_eq!(BOARD_NAME.as_str(), "stm32");
+        assert!(Clock.is_initialized());
+        assert_eq!(Clock.state(), LazyState::Ready);
+    }
+
+    #[test]
+    fn should_wait_for_init_on_another_core() {
+        wildbird::set_context_id(context_id);
+        let cores: std::vec::Vec<_> = (0..4).map(|_| std::thread::spawn(|| Calibration.offset)).collect();
+        for core in cores {
+            assert_eq!(core.join().unwrap(), 7);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Circular dependency")]
+    fn should_detect_reentrant_init() {
+        wildbird::set_context_id(context_id);
+        let _ = *RECURSIVE;
+    }
+}
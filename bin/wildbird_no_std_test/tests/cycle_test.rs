@@ -0,0 +1,18 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Own binary, `set_context_id` is never set here
+/// use:  cargo test -p wildbird_no_std_test --test cycle_test
+mod cycle {
+    use wildbird::prelude::*;
+
+    #[var]
+    fn looped() -> u32 {
+        *LOOPED + 1
+    }
+
+    #[test]
+    #[should_panic(expected = "Circular dependency")]
+    fn should_detect_cycle_without_context_id() {
+        let _ = *LOOPED;
+    }
+}
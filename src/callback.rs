@@ -1,15 +1,31 @@
+use crate::lazy_watch::WatchSink;
 use std::sync::mpsc::SyncSender;
 
 // Callback for lazy initialization
 #[doc(hidden)]
-pub struct Callback<T>(SyncSender<T>);
+pub struct Callback<T>(Sink<T>);
+
+enum Sink<T> {
+    Once(SyncSender<T>),
+    Watch(WatchSink<T>),
+}
 
 impl<T> Callback<T> {
     pub fn new(tx: SyncSender<T>) -> Callback<T> {
-        Callback(tx)
+        Callback(Sink::Once(tx))
+    }
+
+    pub(crate) fn watch(sink: WatchSink<T>) -> Callback<T> {
+        Callback(Sink::Watch(sink))
     }
 
+    /// Publishes value, only the first one is kept unless the var is `#[var(watch)]`
     pub fn call(&self, init: T) {
-        let _ = self.0.send(init);
+        match &self.0 {
+            Sink::Once(tx) => {
+                let _ = tx.send(init);
+            }
+            Sink::Watch(sink) => sink.publish(init),
+        }
     }
-}
\ No newline at end of file
+}
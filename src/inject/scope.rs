@@ -0,0 +1,282 @@
+use std::any::{type_name, Any};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::LazyState;
+
+// Cilcular dependency detection
+thread_local! {
+    static INJECT_STACK: RefCell<VecDeque<String>>  = const { RefCell::new(VecDeque::new()) };
+    static LAST_CYCLE: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+pub struct InjectStack {}
+impl InjectStack {
+
+    /// Pushes id, panics on circular dependency. Id is removed when the guard drops, also on unwind
+    pub fn enter(id: String) -> InjectGuard {
+        if Self::has_id(&id) {
+            let mut cycle = Self::snapshot();
+            cycle.push(id.clone());
+            LAST_CYCLE.with_borrow_mut(|last| *last = Some(cycle));
+            panic!("{}", Self::cilcuar_error(id))
+        }
+        INJECT_STACK.with_borrow_mut(|v| v.push_back(id.clone()));
+        InjectGuard { id }
+    }
+
+    pub fn has_id(id: &String) -> bool {
+        INJECT_STACK.with_borrow(|v| v.contains(id))
+    }
+
+    /// Ids currently being constructed, innermost last
+    pub fn snapshot() -> Vec<String> {
+        INJECT_STACK
+            .try_with(|v| v.try_borrow().map(|v| v.iter().cloned().collect()).unwrap_or_default())
+            .unwrap_or_default()
+    }
+
+    /// Ids of the last circular dependency detected on the thread, repeated id last
+    pub(crate) fn take_cycle() -> Option<Vec<String>> {
+        LAST_CYCLE.try_with(|last| last.take()).ok().flatten()
+    }
+
+    pub(crate) fn take() -> VecDeque<String> {
+        INJECT_STACK.take()
+    }
+
+    pub(crate) fn restore(stack: VecDeque<String>) {
+        INJECT_STACK.set(stack);
+    }
+
+    pub fn cilcuar_error(id: String) -> String {
+        let mut all = INJECT_STACK.with_borrow(|v| v.clone());
+        all.push_back(id);
+        let max_id = all.iter().map(|v| v.len()).max().unwrap_or(60);
+        let max_id = max_id + 10;
+        let line = "-".repeat(max_id / 2);
+
+        let mut message = vec![format!("Circular dependency:\n|{line} < {line}|")];
+        for id in all {
+            let pad = " ".repeat(max_id - id.len());
+            message.push(format!("| {id}{pad} |"));
+        }
+        message.push(format!("|{line} > {line}|\n"));
+        message.join("\n")
+    }
+}
+
+pub struct InjectGuard {
+    id: String,
+}
+
+impl Drop for InjectGuard {
+    fn drop(&mut self) {
+        let _ = INJECT_STACK.try_with(|v| v.borrow_mut().retain(|v| v != &self.id));
+    }
+}
+
+// Isolated service graph
+thread_local! {
+    static INJECT_SCOPE: RefCell<Option<Arc<ScopeState>>> = const { RefCell::new(None) };
+}
+static ACTIVE_SCOPES: AtomicUsize = AtomicUsize::new(0);
+
+/// `Lazy` id, file and line of the declaration, number of the instance built there at runtime
+pub(crate) type ScopeKey = (&'static str, u32, u32);
+
+pub(crate) struct ScopedInstance {
+    instance: Arc<dyn Any + Send + Sync>,
+    // Borrowed as `&'static T`, outlives the scope
+    pinned: AtomicBool,
+}
+
+impl ScopedInstance {
+    fn new<T: Send + Sync + 'static>(instance: Arc<T>) -> ScopedInstance {
+        ScopedInstance {
+            instance,
+            pinned: AtomicBool::new(false),
+        }
+    }
+
+    fn is<T>(&self, instance: &Arc<T>) -> bool {
+        std::ptr::addr_eq(Arc::as_ptr(&self.instance), Arc::as_ptr(instance))
+    }
+}
+
+impl Drop for ScopedInstance {
+    fn drop(&mut self) {
+        if *self.pinned.get_mut() {
+            std::mem::forget(self.instance.clone());
+        }
+    }
+}
+
+#[doc(hidden)]
+#[derive(Default)]
+pub struct ScopeState {
+    instances: RwLock<HashMap<ScopeKey, ScopedInstance>>,
+    states: Mutex<HashMap<ScopeKey, LazyState>>,
+}
+
+impl ScopeState {
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<ScopeKey, ScopedInstance>> {
+        self.instances.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<ScopeKey, ScopedInstance>> {
+        self.instances.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn cast<T: Send + Sync + 'static>(key: ScopeKey, scoped: &ScopedInstance) -> Arc<T> {
+        scoped.instance.clone().downcast::<T>().unwrap_or_else(|_| {
+            panic!("Scoped instance type mismatch for {}:{}#{}, expected {}", key.0, key.1, key.2, type_name::<T>())
+        })
+    }
+
+    pub(crate) fn get<T: Send + Sync + 'static>(&self, key: ScopeKey) -> Option<Arc<T>> {
+        self.read().get(&key).map(|scoped| Self::cast(key, scoped))
+    }
+
+    // Keeps instance inserted first, when constructed concurrently
+    pub(crate) fn insert<T: Send + Sync + 'static>(&self, key: ScopeKey, instance: Arc<T>) -> Arc<T> {
+        let mut instances = self.write();
+        let scoped = instances.entry(key).or_insert_with(|| ScopedInstance::new(instance));
+        Self::cast(key, scoped)
+    }
+
+    /// Reference outliving the scope, the instance is never freed
+    pub(crate) fn pin<T: Send + Sync + 'static>(&self, key: ScopeKey, instance: Arc<T>) -> &'static T {
+        match self.read().get(&key) {
+            Some(scoped) if scoped.is(&instance) => scoped.pinned.store(true, Ordering::Relaxed),
+            // Replaced meanwhile, nothing owns this one
+            _ => std::mem::forget(instance.clone()),
+        }
+        // Safety: pinned entry or the forgotten clone keeps a strong reference forever
+        unsafe { &*Arc::as_ptr(&instance) }
+    }
+
+    /// Init state of the scoped instance, `None` before the first init
+    pub(crate) fn state(&self, key: ScopeKey) -> Option<LazyState> {
+        self.states.lock().unwrap_or_else(|e| e.into_inner()).get(&key).cloned()
+    }
+
+    pub(crate) fn set_state(&self, key: ScopeKey, state: LazyState) {
+        self.states.lock().unwrap_or_else(|e| e.into_inner()).insert(key, state);
+    }
+
+    fn replace<T: Send + Sync + 'static>(&self, key: ScopeKey, instance: Arc<T>) -> Option<ScopedInstance> {
+        self.write().insert(key, ScopedInstance::new(instance))
+    }
+
+    fn restore(&self, key: ScopeKey, previous: Option<ScopedInstance>) {
+        let mut instances = self.write();
+        let replaced = match previous {
+            Some(previous) => instances.insert(key, previous),
+            None => instances.remove(&key),
+        };
+        // Dropped without holding the lock, drop of the instance may access other services
+        drop(instances);
+        drop(replaced);
+    }
+}
+
+/// Thread scoped service graph
+///
+/// While a scope is active, every `Lazy` (except shared ones) and mutable var accessed on the thread
+/// is constructed again inside the scope. Scoped instances are dropped with the scope.
+///
+/// Instances borrowed by reference (`Deref`, `to_ref()`, `&T` injection) are leaked instead,
+/// with everything they hold: the reference is `&'static`, it may outlive the scope.
+/// Their `Drop` never runs, use `instance()` or `Arc<T>` injection where that matters,
+/// e.g. in `#[wildbird::testing::test]` parameters.
+pub struct InjectScope {}
+impl InjectScope {
+    #[inline]
+    pub fn current() -> Option<Arc<ScopeState>> {
+        if ACTIVE_SCOPES.load(Ordering::Relaxed) == 0 {
+            return None;
+        }
+        INJECT_SCOPE.with(|scope| scope.borrow().clone())
+    }
+
+    #[doc(hidden)]
+    pub fn enter(state: Arc<ScopeState>) -> Option<Arc<ScopeState>> {
+        let previous = INJECT_SCOPE.with(|scope| scope.borrow_mut().replace(state));
+        if previous.is_none() {
+            ACTIVE_SCOPES.fetch_add(1, Ordering::AcqRel);
+        }
+        previous
+    }
+
+    #[doc(hidden)]
+    pub fn exit(previous: Option<Arc<ScopeState>>) {
+        let restore_none = previous.is_none();
+        let current = INJECT_SCOPE.with(|scope| std::mem::replace(&mut *scope.borrow_mut(), previous));
+        if current.is_some() && restore_none {
+            ACTIVE_SCOPES.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+
+    /// Enter fresh service graph on the current thread, until the guard is dropped
+    pub fn isolate() -> ScopeGuard {
+        ScopeGuard {
+            stack: InjectStack::take(),
+            previous: Some(Self::enter(Arc::new(ScopeState::default()))),
+        }
+    }
+
+    pub(crate) fn override_instance<T: Send + Sync + 'static>(key: ScopeKey, instance: Arc<T>) -> OverrideGuard {
+        let (scope, entered) = match Self::current() {
+            Some(scope) => (scope, None),
+            None => {
+                let scope = Arc::new(ScopeState::default());
+                (scope.clone(), Some(Self::enter(scope)))
+            }
+        };
+        let previous = scope.replace(key, instance);
+        OverrideGuard {
+            key,
+            scope,
+            previous,
+            entered,
+        }
+    }
+}
+
+/// Exits isolated [`InjectScope`] on drop
+#[must_use]
+pub struct ScopeGuard {
+    // Services under construction outside of the scope
+    stack: VecDeque<String>,
+    previous: Option<Option<Arc<ScopeState>>>,
+}
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        InjectStack::restore(std::mem::take(&mut self.stack));
+        if let Some(previous) = self.previous.take() {
+            InjectScope::exit(previous);
+        }
+    }
+}
+
+/// Restores overridden `Lazy` on drop
+#[must_use]
+pub struct OverrideGuard {
+    key: ScopeKey,
+    scope: Arc<ScopeState>,
+    previous: Option<ScopedInstance>,
+    entered: Option<Option<Arc<ScopeState>>>,
+}
+
+impl Drop for OverrideGuard {
+    fn drop(&mut self) {
+        self.scope.restore(self.key, self.previous.take());
+        if let Some(previous) = self.entered.take() {
+            InjectScope::exit(previous);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use std::fmt::{self, Debug, Display, Formatter};
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Intercepted method call
+pub struct Call {
+    service: &'static str,
+    method: &'static str,
+    started: Instant,
+}
+
+impl Call {
+    #[doc(hidden)]
+    pub fn new(service: &'static str, method: &'static str) -> Call {
+        Call {
+            service,
+            method,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn service(&self) -> &'static str {
+        self.service
+    }
+
+    pub fn method(&self) -> &'static str {
+        self.method
+    }
+
+    /// Time since the call started
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+impl Display for Call {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{}", self.service, self.method)
+    }
+}
+
+/// Hooks around `#[intercept]` service methods
+///
+/// `on_error` is called for methods returning `Result` (or an alias of it) when the call ends
+/// with `Err`, errors without `Debug` are reported as `<error>`.
+/// Interceptors are built once per method with `Default` and shared between threads.
+///
+/// Interceptors only observe the call, they can't re-invoke it or change the result,
+/// so retries belong in the method body.
+///
+/// # Example
+/// ```
+/// use wildbird::prelude::*;
+/// use wildbird::intercept::Call;
+///
+/// #[derive(Default)]
+/// struct Logging;
+///
+/// impl Interceptor for Logging {
+///     fn before(&self, call: &Call) {
+///         println!("{call} started");
+///     }
+/// }
+///
+/// #[service(construct = "init")]
+/// struct Users {}
+///
+/// #[intercept(with = Logging)]
+/// impl Users {
+///     fn init() -> Users {
+///         Users {}
+///     }
+///
+///     fn find(&self, id: u32) -> Result<String, String> {
+///         Ok(format!("user {id}"))
+///     }
+/// }
+///
+/// assert_eq!(Ok("user 1".to_string()), Users.find(1));
+/// ```
+pub trait Interceptor: Send + Sync {
+    fn before(&self, _call: &Call) {}
+
+    fn after(&self, _call: &Call) {}
+
+    fn on_error(&self, _call: &Call, _error: &dyn Debug) {}
+}
+
+#[inline(always)]
+#[doc(hidden)]
+// Pins async block output type, required by `?` in intercepted bodies
+pub fn returning<R, F: Future<Output = R>>(future: F) -> F {
+    future
+}
+
+#[doc(hidden)]
+// Interceptors of one method, built on first call
+pub struct Interceptors<I>(OnceLock<I>);
+
+impl<I: Default> Interceptors<I> {
+    pub const fn new() -> Interceptors<I> {
+        Interceptors(OnceLock::new())
+    }
+
+    pub fn get(&self) -> &I {
+        self.0.get_or_init(I::default)
+    }
+}
+
+impl<I: Default> Default for Interceptors<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[doc(hidden)]
+pub struct ErrorProbe<'a, R>(pub &'a R);
+
+// Autoref specialization, called as `(&&&probe).error()`:
+// Debug errors first, then any other `Result`, then non `Result` returns
+#[doc(hidden)]
+pub trait ErrorDebug {
+    fn error(&self) -> Option<&dyn Debug>;
+}
+
+impl<T, E: Debug> ErrorDebug for &&ErrorProbe<'_, Result<T, E>> {
+    fn error(&self) -> Option<&dyn Debug> {
+        self.0.as_ref().err().map(|e| e as &dyn Debug)
+    }
+}
+
+#[doc(hidden)]
+pub trait ErrorOpaque {
+    fn error(&self) -> Option<&dyn Debug>;
+}
+
+impl<T, E> ErrorOpaque for &ErrorProbe<'_, Result<T, E>> {
+    fn error(&self) -> Option<&dyn Debug> {
+        self.0.as_ref().err().map(|_| &"<error>" as &dyn Debug)
+    }
+}
+
+#[doc(hidden)]
+pub trait NoError {
+    fn error(&self) -> Option<&dyn Debug>;
+}
+
+impl<R> NoError for ErrorProbe<'_, R> {
+    fn error(&self) -> Option<&dyn Debug> {
+        None
+    }
+}
@@ -1,94 +1,431 @@
-use crate::inject::InjectStack;
+use crate::inject::{InjectScope, InjectStack, OverrideGuard, ScopeKey, ScopeState};
+#[cfg(feature = "lazy-stats")]
+use crate::metric::{LazyStats, StatsRecord};
+use crate::panics;
+use crate::{InitError, InitPolicy, LazyState};
 use std::fmt::{self, Debug, Display, Formatter};
 use std::ops::Deref;
-use std::panic::Location;
-use std::sync::{Arc, OnceLock};
+use std::panic::{self, AssertUnwindSafe, Location};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
 
 #[doc(hidden)]
 pub struct Lazy<T> {
     instance: OnceLock<Arc<T>>,
     init: fn() -> T,
     id: (&'static str, u32),
+    // Tells apart lazies built at one call site, `0` for declared ones
+    instance_no: AtomicU32,
+    shared: bool,
+    tags: &'static [&'static str],
+    policy: InitPolicy,
+    sync: OnceLock<Box<InitSync<T>>>,
+    #[cfg(feature = "lazy-stats")]
+    stats: OnceLock<Arc<StatsRecord>>,
 }
 
-impl<T> Deref for Lazy<T> {
+/// Ignored with `std`, cycles are detected per thread
+pub fn set_context_id(_context_id: fn() -> usize) {}
+
+type InitCallback<T> = Arc<dyn Fn(&T) + Send + Sync>;
+
+// Init coordination, allocated on first init or callback registration
+struct InitSync<T> {
+    state: Mutex<LazyState>,
+    ready: Condvar,
+    on_init: Mutex<OnInit<T>>,
+}
+
+impl<T> InitSync<T> {
+    fn new(state: LazyState, done: bool) -> Box<Self> {
+        Box::new(Self {
+            state: Mutex::new(state),
+            ready: Condvar::new(),
+            on_init: Mutex::new(OnInit { callbacks: Vec::new(), done }),
+        })
+    }
+}
+
+struct OnInit<T> {
+    callbacks: Vec<InitCallback<T>>,
+    // Global instance built, callbacks registered later run immediately
+    done: bool,
+}
+
+impl<T: Send + Sync + 'static> Deref for Lazy<T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        self._get().as_ref()
+        self.to_ref()
     }
 }
 
-impl<T: Display> Display for Lazy<T> {
+impl<T: Display + Send + Sync + 'static> Display for Lazy<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         if let Some(inner) = self._get_opt() {
-            return Display::fmt(inner, f);
+            return Display::fmt(inner.as_ref(), f);
         };
         Display::fmt("(Not initialized)", f)
     }
 }
 
-impl<T: Debug> Debug for Lazy<T> {
+impl<T: Debug + Send + Sync + 'static> Debug for Lazy<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         if let Some(inner) = self._get_opt() {
-            return Debug::fmt(inner, f);
+            return Debug::fmt(inner.as_ref(), f);
         };
         Debug::fmt("(Not initialized) - use to_ref()", f)
     }
 }
 
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize + Send + Sync + 'static> serde::Serialize for Lazy<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self._get_opt() {
+            Some(inner) => inner.as_ref().serialize(serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
 impl<T> Lazy<T> {
     #[track_caller]
     pub const fn new(init: fn() -> T) -> Lazy<T> {
-        let caller = Location::caller();
-        let file = caller.file();
-        let line = caller.line();
+        Self::build(init, Location::caller(), false)
+    }
+
+    /// Lazy shared by every [`InjectScope`](crate::InjectScope), never constructed again in scope
+    #[track_caller]
+    pub const fn shared(init: fn() -> T) -> Lazy<T> {
+        Self::build(init, Location::caller(), true)
+    }
 
+    pub(crate) const fn build(init: fn() -> T, caller: &'static Location<'static>, shared: bool) -> Lazy<T> {
         Self {
             instance: OnceLock::new(),
             init,
-            id: (file, line),
+            id: (caller.file(), caller.line()),
+            instance_no: AtomicU32::new(0),
+            shared,
+            tags: &[],
+            policy: InitPolicy::Retry,
+            sync: OnceLock::new(),
+            #[cfg(feature = "lazy-stats")]
+            stats: OnceLock::new(),
         }
     }
 
-    fn _get(&self) -> &Arc<T> {
-        match self._get_opt() {
+    pub const fn with_policy(mut self, policy: InitPolicy) -> Lazy<T> {
+        self.policy = policy;
+        self
+    }
+
+    /// Tags used by [`find_by_tag`](crate::find_by_tag)
+    pub const fn with_tags(mut self, tags: &'static [&'static str]) -> Lazy<T> {
+        self.tags = tags;
+        self
+    }
+
+    pub fn tags(&self) -> &'static [&'static str] {
+        self.tags
+    }
+
+    /// Numbered on first use, for lazies built at runtime by one call site (`LazyWith`)
+    pub(crate) const fn numbered(mut self) -> Lazy<T> {
+        self.instance_no = AtomicU32::new(UNNUMBERED);
+        self
+    }
+
+    fn _key(&self) -> ScopeKey {
+        let mut instance_no = self.instance_no.load(Ordering::Relaxed);
+        if instance_no == UNNUMBERED {
+            static NEXT: AtomicU32 = AtomicU32::new(1);
+            let next = NEXT.fetch_add(1, Ordering::Relaxed);
+            instance_no = match self.instance_no.compare_exchange(UNNUMBERED, next, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => next,
+                Err(current) => current,
+            };
+        }
+        (self.id.0, self.id.1, instance_no)
+    }
+
+    pub(crate) fn id(&self) -> String {
+        match self._key() {
+            (file, line, 0) => format!("{file}:{line}"),
+            (file, line, instance_no) => format!("{file}:{line}#{instance_no}"),
+        }
+    }
+}
+
+const UNNUMBERED: u32 = u32::MAX;
+
+impl<T: Send + Sync + 'static> Lazy<T> {
+    #[inline]
+    fn _get(&self, init: impl FnOnce() -> T) -> &Arc<T> {
+        match self.instance.get() {
             Some(instance) => instance,
-            None => {
-                let id = self.id();
-                if InjectStack::has_id(&id) {
-                    panic!("{}", InjectStack::cilcuar_error(id))
-                }
-                InjectStack::push_id(id.clone());
-                let instance = self.instance.get_or_init(|| Arc::new((self.init)()));
-                InjectStack::remove(&id);
-                instance
+            None => self._get_init(init),
+        }
+    }
+
+    #[cold]
+    fn _get_init(&self, init: impl FnOnce() -> T) -> &Arc<T> {
+        let _guard = InjectStack::enter(self.id());
+        let state = self._state().clone();
+        self._fail_fast(state);
+        let mut built = false;
+        let instance = self.instance.get_or_init(|| {
+            // Waiters blocked on the first failed init
+            let state = self._state().clone();
+            self._fail_fast(state);
+            built = true;
+            Arc::new(self._init(init))
+        });
+        if built {
+            // Waiters check the instance under the state lock
+            drop(self._state());
+            self._sync().ready.notify_all();
+            let callbacks = {
+                let mut on_init = self._on_init();
+                on_init.done = true;
+                on_init.callbacks.clone()
+            };
+            Self::_run_on_init(callbacks, instance);
+        }
+        instance
+    }
+
+    fn _run_on_init(callbacks: Vec<InitCallback<T>>, instance: &T) {
+        for callback in callbacks {
+            callback(instance);
+        }
+    }
+
+    fn _sync(&self) -> &InitSync<T> {
+        self.sync.get_or_init(|| InitSync::new(LazyState::Uninit, false))
+    }
+
+    fn _state(&self) -> MutexGuard<'_, LazyState> {
+        self._sync().state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn _on_init(&self) -> MutexGuard<'_, OnInit<T>> {
+        self._sync().on_init.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn _fail_fast(&self, state: LazyState) {
+        if let (InitPolicy::FailFast, LazyState::Failed(message)) = (self.policy, state) {
+            panic!("Lazy initialization failed ({}): {message}", self.id())
+        }
+    }
+
+    fn _init(&self, init: impl FnOnce() -> T) -> T {
+        #[cfg(feature = "lazy-stats")]
+        let start = Instant::now();
+        let value = self._build(init, |state| *self._state() = state);
+        #[cfg(feature = "lazy-stats")]
+        self._stats().initialized(start.elapsed());
+        value
+    }
+
+    // Runs init, reporting the state of the global or scoped instance
+    fn _build(&self, init: impl FnOnce() -> T, set_state: impl Fn(LazyState)) -> T {
+        set_state(LazyState::Initializing);
+        match panic::catch_unwind(AssertUnwindSafe(init)) {
+            Ok(value) => {
+                set_state(LazyState::Ready);
+                value
+            }
+            Err(payload) => {
+                set_state(LazyState::Failed(panics::message(payload.as_ref())));
+                panic::resume_unwind(payload)
             }
         }
     }
 
-    fn id(&self) -> String {
-        format!("{}:{}", self.id.0, self.id.1)
+    /// State of the instance visible on the current thread
+    pub fn state(&self) -> LazyState {
+        if self._get_opt().is_some() {
+            return LazyState::Ready;
+        }
+        if let Some(scope) = InjectScope::current().filter(|_| !self.shared) {
+            return scope.state(self._key()).unwrap_or(LazyState::Uninit);
+        }
+        self._state().clone()
+    }
+
+    #[cfg(feature = "lazy-stats")]
+    fn _stats(&self) -> &Arc<StatsRecord> {
+        self.stats
+            .get_or_init(|| StatsRecord::register(self.id(), std::any::type_name::<T>()))
+    }
+
+    /// Init and access metrics, empty until the first access
+    #[cfg(feature = "lazy-stats")]
+    pub fn stats(&self) -> LazyStats {
+        match self.stats.get() {
+            Some(record) => record.stats(),
+            None => LazyStats {
+                id: self.id(),
+                type_name: std::any::type_name::<T>(),
+                first_access: None,
+                init_duration: None,
+                built_by: None,
+                accesses: cfg!(feature = "timed-access").then_some(0),
+            },
+        }
+    }
+
+    fn _get_scoped(&self, scope: &ScopeState) -> Option<Arc<T>> {
+        if let Some(instance) = scope.get(self._key()) {
+            return Some(instance);
+        }
+        if self.shared {
+            return None;
+        }
+        let guard = InjectStack::enter(self.id());
+        self._fail_fast(scope.state(self._key()).unwrap_or(LazyState::Uninit));
+        let instance = Arc::new(self._build(self.init, |state| scope.set_state(self._key(), state)));
+        drop(guard);
+        // Insert under the callback lock, so `on_init` sees either the list or the instance
+        let (inserted, callbacks) = {
+            let on_init = self._on_init();
+            let inserted = scope.insert(self._key(), instance.clone());
+            match Arc::ptr_eq(&inserted, &instance) {
+                true => (inserted, on_init.callbacks.clone()),
+                false => (inserted, Vec::new()),
+            }
+        };
+        Self::_run_on_init(callbacks, &inserted);
+        Some(inserted)
+    }
+
+    fn _get_opt(&self) -> Option<Arc<T>> {
+        if let Some(scope) = InjectScope::current() {
+            if let Some(instance) = scope.get(self._key()) {
+                return Some(instance);
+            }
+            if !self.shared {
+                return None;
+            }
+        }
+        self.instance.get().cloned()
     }
 
-    fn _get_opt(&self) -> Option<&Arc<T>> {
-        self.instance.get()
+    /// Checks instance without constructing it
+    pub fn is_initialized(&self) -> bool {
+        self._get_opt().is_some()
+    }
+
+    /// Instance if already constructed, never starts init
+    pub fn try_get(&self) -> Option<Arc<T>> {
+        self._get_opt()
+    }
+
+    /// Blocks until another thread constructs the instance, `None` on timeout
+    pub fn wait_initialized(&self, timeout: Duration) -> Option<Arc<T>> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self._state();
+        loop {
+            if let Some(instance) = self._get_opt() {
+                return Some(instance);
+            }
+            let remaining = deadline.checked_duration_since(Instant::now())?;
+            state = match self._sync().ready.wait_timeout(state, remaining) {
+                Ok((state, _)) => state,
+                Err(e) => e.into_inner().0,
+            };
+        }
+    }
+
+    /// Callback run after each construction, immediately when already constructed
+    pub fn on_init(&self, callback: impl Fn(&T) + Send + Sync + 'static) {
+        let callback: InitCallback<T> = Arc::new(callback);
+        // Builders take the list under the same lock, the callback runs exactly once
+        let instance = {
+            let mut on_init = self._on_init();
+            on_init.callbacks.push(callback.clone());
+            match InjectScope::current().map(|scope| scope.get(self._key())) {
+                Some(Some(instance)) => Some(instance),
+                Some(None) if !self.shared => None,
+                _ => on_init.done.then(|| self.instance.get().cloned()).flatten(),
+            }
+        };
+        if let Some(instance) = instance {
+            callback(&instance);
+        }
+    }
+
+    /// Replace instance on the current thread, until the guard is dropped
+    ///
+    /// Override starts [`InjectScope`](crate::InjectScope) if none is active,
+    /// so services depending on this one are constructed again with the override.
+    pub fn set_override(&self, value: impl Into<T>) -> OverrideGuard {
+        InjectScope::override_instance(self._key(), Arc::new(value.into()))
     }
 
     pub fn clone_lazy(&self) -> Self {
         let instance = self.instance();
         Self {
             instance: OnceLock::from(instance),
-            init: self.init.clone(),
-            id: self.id.clone(),
+            init: self.init,
+            id: self.id,
+            instance_no: AtomicU32::new(self._key().2),
+            shared: self.shared,
+            tags: self.tags,
+            policy: self.policy,
+            sync: OnceLock::from(InitSync::new(LazyState::Ready, true)),
+            // Same record, a clone is not another `Lazy` in `lazy_stats()`
+            #[cfg(feature = "lazy-stats")]
+            stats: OnceLock::from(self._stats().clone()),
         }
     }
 
     pub fn instance(&self) -> Arc<T> {
-        self._get().clone()
+        self.instance_with(self.init)
+    }
+
+    /// [`instance`](Lazy::instance) building the global instance with `init`, used by `LazyWith`
+    pub(crate) fn instance_with(&self, init: impl FnOnce() -> T) -> Arc<T> {
+        #[cfg(feature = "lazy-stats")]
+        self._stats().access();
+        if let Some(scope) = InjectScope::current() {
+            if let Some(instance) = self._get_scoped(&scope) {
+                return instance;
+            }
+        }
+        self._get(init).clone()
+    }
+
+    /// [`instance`](Lazy::instance) returning init failure instead of panicking
+    pub fn try_instance(&self) -> Result<Arc<T>, InitError> {
+        // Catching swaps the process panic hook, only worth it around an init
+        if let Some(instance) = self._get_opt() {
+            #[cfg(feature = "lazy-stats")]
+            self._stats().access();
+            return Ok(instance);
+        }
+        panics::catch(|| self.instance()).map_err(|caught| {
+            panics::take_raised().unwrap_or(InitError::Panicked {
+                message: caught.message,
+                location: caught.location,
+            })
+        })
     }
 
     pub fn to_ref(&self) -> &T {
-        self._get().as_ref()
+        self.to_ref_with(self.init)
+    }
+
+    pub(crate) fn to_ref_with(&self, init: impl FnOnce() -> T) -> &T {
+        #[cfg(feature = "lazy-stats")]
+        self._stats().access();
+        if let Some(scope) = InjectScope::current() {
+            if let Some(instance) = self._get_scoped(&scope) {
+                return scope.pin(self._key(), instance);
+            }
+        }
+        self._get(init).as_ref()
     }
 }
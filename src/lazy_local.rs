@@ -0,0 +1,59 @@
+use crate::inject::InjectStack;
+use std::cell::RefCell;
+use std::panic::Location;
+use std::thread::LocalKey;
+
+#[doc(hidden)]
+pub type LocalSlot<T> = RefCell<Option<T>>;
+
+/// Lazy value built once per thread, `T` doesn't need `Sync`
+///
+/// Generated by `#[var(thread_local)]`, the value is reached with [`with`](LazyLocal::with)
+/// and [`with_mut`](LazyLocal::with_mut).
+pub struct LazyLocal<T: 'static> {
+    slot: &'static LocalKey<LocalSlot<T>>,
+    init: fn() -> T,
+    id: (&'static str, u32),
+}
+
+impl<T: 'static> LazyLocal<T> {
+    #[track_caller]
+    pub const fn new(slot: &'static LocalKey<LocalSlot<T>>, init: fn() -> T) -> LazyLocal<T> {
+        let caller = Location::caller();
+        Self {
+            slot,
+            init,
+            id: (caller.file(), caller.line()),
+        }
+    }
+
+    pub(crate) fn id(&self) -> String {
+        format!("{}:{}", self.id.0, self.id.1)
+    }
+
+    fn _init(&self) {
+        if self.is_initialized() {
+            return;
+        }
+        let _guard = InjectStack::enter(self.id());
+        let value = (self.init)();
+        self.slot.with_borrow_mut(|slot| *slot = Some(value));
+    }
+
+    /// Runs `f` with the value of the current thread, builds it on first use
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self._init();
+        self.slot.with_borrow(|slot| f(slot.as_ref().expect("thread local value")))
+    }
+
+    /// Same as [`with`](LazyLocal::with), nested access to the same var panics
+    pub fn with_mut<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        self._init();
+        self.slot.with_borrow_mut(|slot| f(slot.as_mut().expect("thread local value")))
+    }
+
+    /// Checks value of the current thread
+    pub fn is_initialized(&self) -> bool {
+        self.slot.with_borrow(|slot| slot.is_some())
+    }
+}
@@ -0,0 +1,387 @@
+use crate::inject::{InjectScope, InjectStack, ScopeKey};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::panic::Location;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+type Listener<T> = Box<dyn Fn(&T) + Send + Sync>;
+
+/// Lock holding the value of [`LazyMut`] or [`LazyMutAsync`]
+trait MutLock<T>: Send + Sync + 'static {
+    fn new(value: T) -> Self;
+}
+
+impl<T: Send + Sync + 'static> MutLock<T> for RwLock<T> {
+    fn new(value: T) -> Self {
+        RwLock::new(value)
+    }
+}
+
+struct MutValue<L> {
+    lock: L,
+    version: AtomicU64,
+}
+
+/// Init, scoping and change listeners shared by the sync and async mutable var
+struct MutCell<T, L> {
+    value: OnceLock<MutValue<L>>,
+    init: fn() -> T,
+    listeners: Mutex<Vec<Listener<T>>>,
+    id: ScopeKey,
+}
+
+impl<T, L> MutCell<T, L> {
+    const fn new(init: fn() -> T, caller: &'static Location<'static>) -> MutCell<T, L> {
+        Self {
+            value: OnceLock::new(),
+            init,
+            listeners: Mutex::new(Vec::new()),
+            id: (caller.file(), caller.line(), 0),
+        }
+    }
+
+    fn id(&self) -> String {
+        format!("{}:{}", self.id.0, self.id.1)
+    }
+
+    fn on_change(&self, listener: impl Fn(&T) + Send + Sync + 'static) {
+        self.listeners.lock().unwrap_or_else(|e| e.into_inner()).push(Box::new(listener));
+    }
+
+    // Called by the write guard before it unlocks, no other reader sees the value first
+    fn notify(&self, value: &MutValue<L>, current: &T) {
+        value.version.fetch_add(1, Ordering::AcqRel);
+        let listeners = self.listeners.lock().unwrap_or_else(|e| e.into_inner());
+        for listener in listeners.iter() {
+            listener(current);
+        }
+    }
+}
+
+/// Global value, or the scoped one kept alive until the holder drops
+enum MutRef<'a, L> {
+    Global(&'a MutValue<L>),
+    Scoped(Arc<MutValue<L>>),
+}
+
+impl<L> Deref for MutRef<'_, L> {
+    type Target = MutValue<L>;
+
+    fn deref(&self) -> &MutValue<L> {
+        match self {
+            MutRef::Global(value) => value,
+            MutRef::Scoped(value) => value,
+        }
+    }
+}
+
+impl<'a, L> MutRef<'a, L> {
+    /// Lock borrowed for `'a`
+    ///
+    /// # Safety
+    /// The borrow must not outlive `self`, guards keep their `MutRef` in a field dropped after the lock guard.
+    unsafe fn lock(&self) -> &'a L {
+        &*(&self.lock as *const L)
+    }
+}
+
+impl<T, L: MutLock<T>> MutCell<T, L> {
+    /// Value of the current [`InjectScope`] or the global one, without init
+    fn current(&self) -> Option<MutRef<'_, L>> {
+        match InjectScope::current() {
+            Some(scope) => scope.get(self.id).map(MutRef::Scoped),
+            None => self.value.get().map(MutRef::Global),
+        }
+    }
+
+    #[inline]
+    fn get(&self) -> MutRef<'_, L> {
+        if let Some(scope) = InjectScope::current() {
+            return MutRef::Scoped(match scope.get(self.id) {
+                Some(value) => value,
+                None => {
+                    let _guard = InjectStack::enter(self.id());
+                    scope.insert(self.id, Arc::new(self._build()))
+                }
+            });
+        }
+        MutRef::Global(match self.value.get() {
+            Some(value) => value,
+            None => self._init(),
+        })
+    }
+
+    #[cold]
+    fn _init(&self) -> &MutValue<L> {
+        let _guard = InjectStack::enter(self.id());
+        self.value.get_or_init(|| self._build())
+    }
+
+    fn _build(&self) -> MutValue<L> {
+        MutValue {
+            lock: L::new((self.init)()),
+            version: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Mutable global behind `RwLock`, generated by `#[var(mutable)]`
+///
+/// Constructed again in [`InjectScope`](crate::InjectScope), writes inside the scope don't leak out of it,
+/// guards keep the scoped value alive and it's freed with the scope.
+/// Poisoned lock is recovered, the value is left as the panicking writer changed it.
+pub struct LazyMut<T>(MutCell<T, RwLock<T>>);
+
+impl<T: Display + Send + Sync + 'static> Display for LazyMut<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0.current() {
+            Some(value) => Display::fmt(&*read(&value.lock), f),
+            None => Display::fmt("(Not initialized)", f),
+        }
+    }
+}
+
+impl<T: Debug + Send + Sync + 'static> Debug for LazyMut<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0.current() {
+            Some(value) => Debug::fmt(&*read(&value.lock), f),
+            None => Debug::fmt("(Not initialized) - use read()", f),
+        }
+    }
+}
+
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(|e| e.into_inner())
+}
+
+impl<T> LazyMut<T> {
+    #[track_caller]
+    pub const fn new(init: fn() -> T) -> LazyMut<T> {
+        Self(MutCell::new(init, Location::caller()))
+    }
+
+    pub(crate) fn id(&self) -> String {
+        self.0.id()
+    }
+
+    /// Called with the new value after each write, before the write lock is released, must not access the same var
+    pub fn on_change(&self, listener: impl Fn(&T) + Send + Sync + 'static) {
+        self.0.on_change(listener)
+    }
+}
+
+impl<T: Send + Sync + 'static> LazyMut<T> {
+    /// Runs with the value lock of the current scope, `None` until initialized
+    pub(crate) fn _with_current<R>(&self, with: impl FnOnce(&RwLock<T>) -> R) -> Option<R> {
+        self.0.current().map(|value| with(&value.lock))
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.0.current().is_some()
+    }
+
+    pub fn read(&self) -> MutReadGuard<'_, T> {
+        let value = self.0.get();
+        // Safety: `value` is dropped after the lock guard
+        let guard = read(unsafe { value.lock() });
+        MutReadGuard { guard, _value: value }
+    }
+
+    /// Write guard, change listeners run when it's dropped
+    pub fn write(&self) -> MutGuard<'_, T> {
+        let value = self.0.get();
+        // Safety: `value` is dropped after the lock guard
+        let guard = unsafe { value.lock() }.write().unwrap_or_else(|e| e.into_inner());
+        MutGuard { guard, value, lazy: self }
+    }
+
+    pub fn update<R>(&self, change: impl FnOnce(&mut T) -> R) -> R {
+        change(&mut *self.write())
+    }
+
+    /// Sets new value, returns the previous one
+    pub fn replace(&self, value: T) -> T {
+        std::mem::replace(&mut *self.write(), value)
+    }
+
+    /// Number of writes since init
+    pub fn version(&self) -> u64 {
+        self.0.current().map_or(0, |value| value.version.load(Ordering::Acquire))
+    }
+}
+
+/// Read guard of [`LazyMut`]
+pub struct MutReadGuard<'a, T> {
+    guard: RwLockReadGuard<'a, T>,
+    _value: MutRef<'a, RwLock<T>>,
+}
+
+impl<T> Deref for MutReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+/// Write guard of [`LazyMut`]
+pub struct MutGuard<'a, T> {
+    guard: RwLockWriteGuard<'a, T>,
+    value: MutRef<'a, RwLock<T>>,
+    lazy: &'a LazyMut<T>,
+}
+
+impl<T> Deref for MutGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for MutGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for MutGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lazy.0.notify(&self.value, &self.guard);
+    }
+}
+
+#[cfg(feature = "tokio")]
+pub use self::tokio_lock::{LazyMutAsync, MutAsyncGuard, MutAsyncReadGuard};
+
+#[cfg(feature = "tokio")]
+mod tokio_lock {
+    use super::{MutCell, MutLock, MutRef};
+    use std::fmt::{self, Debug, Formatter};
+    use std::ops::{Deref, DerefMut};
+    use std::panic::Location;
+    use std::sync::atomic::Ordering;
+    use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+    impl<T: Send + Sync + 'static> MutLock<T> for RwLock<T> {
+        fn new(value: T) -> Self {
+            RwLock::new(value)
+        }
+    }
+
+    /// Mutable global behind `tokio::sync::RwLock`, generated by `#[var(mutable = "async")]`
+    ///
+    /// Constructed again in [`InjectScope`](crate::InjectScope), as [`LazyMut`](crate::LazyMut).
+    pub struct LazyMutAsync<T>(MutCell<T, RwLock<T>>);
+
+    impl<T: Send + Sync + 'static> Debug for LazyMutAsync<T> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            f.debug_struct("LazyMutAsync")
+                .field("initialized", &self.is_initialized())
+                .field("version", &self.version())
+                .finish()
+        }
+    }
+
+    impl<T> LazyMutAsync<T> {
+        #[track_caller]
+        pub const fn new(init: fn() -> T) -> LazyMutAsync<T> {
+            Self(MutCell::new(init, Location::caller()))
+        }
+
+        pub(crate) fn id(&self) -> String {
+            self.0.id()
+        }
+
+        /// Called with the new value after each write, before the write lock is released, must not access the same var
+        pub fn on_change(&self, listener: impl Fn(&T) + Send + Sync + 'static) {
+            self.0.on_change(listener)
+        }
+    }
+
+    impl<T: Send + Sync + 'static> LazyMutAsync<T> {
+        /// Runs with the value lock of the current scope, `None` until initialized
+        pub(crate) fn _with_current<R>(&self, with: impl FnOnce(&RwLock<T>) -> R) -> Option<R> {
+            self.0.current().map(|value| with(&value.lock))
+        }
+
+        pub(crate) fn _init(&self) {
+            self.0.get();
+        }
+
+        pub fn is_initialized(&self) -> bool {
+            self.0.current().is_some()
+        }
+
+        pub async fn read(&self) -> MutAsyncReadGuard<'_, T> {
+            let value = self.0.get();
+            // Safety: `value` is dropped after the lock guard
+            let guard = unsafe { value.lock() }.read().await;
+            MutAsyncReadGuard { guard, _value: value }
+        }
+
+        /// Write guard, change listeners run when it's dropped
+        pub async fn write(&self) -> MutAsyncGuard<'_, T> {
+            let value = self.0.get();
+            // Safety: `value` is dropped after the lock guard
+            let guard = unsafe { value.lock() }.write().await;
+            MutAsyncGuard { guard, value, lazy: self }
+        }
+
+        pub async fn update<R>(&self, change: impl FnOnce(&mut T) -> R) -> R {
+            change(&mut *self.write().await)
+        }
+
+        /// Sets new value, returns the previous one
+        pub async fn replace(&self, value: T) -> T {
+            std::mem::replace(&mut *self.write().await, value)
+        }
+
+        /// Number of writes since init
+        pub fn version(&self) -> u64 {
+            self.0.current().map_or(0, |value| value.version.load(Ordering::Acquire))
+        }
+    }
+
+    /// Read guard of [`LazyMutAsync`]
+    pub struct MutAsyncReadGuard<'a, T> {
+        guard: RwLockReadGuard<'a, T>,
+        _value: MutRef<'a, RwLock<T>>,
+    }
+
+    impl<T> Deref for MutAsyncReadGuard<'_, T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            &self.guard
+        }
+    }
+
+    /// Write guard of [`LazyMutAsync`]
+    pub struct MutAsyncGuard<'a, T> {
+        guard: RwLockWriteGuard<'a, T>,
+        value: MutRef<'a, RwLock<T>>,
+        lazy: &'a LazyMutAsync<T>,
+    }
+
+    impl<T> Deref for MutAsyncGuard<'_, T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            &self.guard
+        }
+    }
+
+    impl<T> DerefMut for MutAsyncGuard<'_, T> {
+        fn deref_mut(&mut self) -> &mut T {
+            &mut self.guard
+        }
+    }
+
+    impl<T> Drop for MutAsyncGuard<'_, T> {
+        fn drop(&mut self) {
+            self.lazy.0.notify(&self.value, &self.guard);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use crate::{InitPolicy, LazyState};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::ops::Deref;
+use core::panic::Location;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use spin::{Mutex, Once};
+
+/// `Lazy` without `std`, backed by spin [`Once`]
+///
+/// Access while the init runs is a circular dependency and panics.
+/// On multi-core targets set [`set_context_id`], access racing with init on another core then
+/// spins until the instance is ready, only access from the initializing core panics.
+/// Init panic always fails fast.
+#[doc(hidden)]
+pub struct Lazy<T> {
+    instance: Once<Arc<T>>,
+    init: fn() -> T,
+    id: (&'static str, u32),
+    tags: &'static [&'static str],
+    state: Mutex<LazyState>,
+    // Context running init, `NO_OWNER` when idle, `ANY_OWNER` without context id
+    owner: AtomicUsize,
+}
+
+const NO_OWNER: usize = usize::MAX;
+const ANY_OWNER: usize = usize::MAX - 1;
+
+static CONTEXT_ID: Once<fn() -> usize> = Once::new();
+
+/// Id of the current core or thread, lets other cores wait for a running init without `std`
+///
+/// Set once at startup, later calls are ignored.
+/// Without it every access during init panics as circular dependency, which is right on a single core.
+pub fn set_context_id(context_id: fn() -> usize) {
+    CONTEXT_ID.call_once(|| context_id);
+}
+
+fn _context_id() -> Option<usize> {
+    CONTEXT_ID.get().map(|context_id| context_id())
+}
+
+impl<T> Deref for Lazy<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self._get().as_ref()
+    }
+}
+
+impl<T: Display> Display for Lazy<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(inner) = self.instance.get() {
+            return Display::fmt(inner, f);
+        };
+        Display::fmt("(Not initialized)", f)
+    }
+}
+
+impl<T: Debug> Debug for Lazy<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(inner) = self.instance.get() {
+            return Debug::fmt(inner, f);
+        };
+        Debug::fmt("(Not initialized) - use to_ref()", f)
+    }
+}
+
+// Lives on the init stack frame, marks init as failed when it unwinds
+struct InitGuard<'a> {
+    state: &'a Mutex<LazyState>,
+    owner: &'a AtomicUsize,
+}
+
+impl Drop for InitGuard<'_> {
+    fn drop(&mut self) {
+        self.owner.store(NO_OWNER, Ordering::Release);
+        let mut state = self.state.lock();
+        if *state == LazyState::Initializing {
+            *state = LazyState::Failed("init panicked".to_string());
+        }
+    }
+}
+
+impl<T> Lazy<T> {
+    #[track_caller]
+    pub const fn new(init: fn() -> T) -> Lazy<T> {
+        let caller = Location::caller();
+        Self {
+            instance: Once::new(),
+            init,
+            id: (caller.file(), caller.line()),
+            tags: &[],
+            state: Mutex::new(LazyState::Uninit),
+            owner: AtomicUsize::new(NO_OWNER),
+        }
+    }
+
+    /// Same as [`Lazy::new`], there is no `InjectScope` without `std`
+    #[track_caller]
+    pub const fn shared(init: fn() -> T) -> Lazy<T> {
+        Self::new(init)
+    }
+
+    /// Only [`InitPolicy::FailFast`], spin `Once` can't run init again
+    ///
+    /// `Retry` fails const evaluation of the static.
+    pub const fn with_policy(self, policy: InitPolicy) -> Lazy<T> {
+        assert!(matches!(policy, InitPolicy::FailFast), "InitPolicy::Retry needs `std` feature");
+        self
+    }
+
+    pub const fn with_tags(mut self, tags: &'static [&'static str]) -> Lazy<T> {
+        self.tags = tags;
+        self
+    }
+
+    pub fn tags(&self) -> &'static [&'static str] {
+        self.tags
+    }
+
+    fn _get(&self) -> &Arc<T> {
+        if let Some(instance) = self.instance.get() {
+            return instance;
+        }
+        if let LazyState::Failed(message) = &*self.state.lock() {
+            panic!("Lazy initialization failed ({}): {message}", self.id())
+        }
+        let context = _context_id();
+        let owner = self.owner.load(Ordering::Acquire);
+        if owner != NO_OWNER && context.map_or(true, |context| context == owner) {
+            panic!("Circular dependency ({})", self.id())
+        }
+        // Init racing on another core spins in `call_once` until the instance is ready
+        self.instance.call_once(|| {
+            self.owner.store(context.unwrap_or(ANY_OWNER), Ordering::Release);
+            *self.state.lock() = LazyState::Initializing;
+            let guard = InitGuard { state: &self.state, owner: &self.owner };
+            let instance = Arc::new((self.init)());
+            *guard.state.lock() = LazyState::Ready;
+            instance
+        })
+    }
+
+    pub fn state(&self) -> LazyState {
+        self.state.lock().clone()
+    }
+
+    pub(crate) fn id(&self) -> String {
+        format!("{}:{}", self.id.0, self.id.1)
+    }
+
+    /// Checks instance without constructing it
+    pub fn is_initialized(&self) -> bool {
+        self.instance.is_completed()
+    }
+
+    /// Instance if already constructed, never starts init
+    pub fn try_get(&self) -> Option<Arc<T>> {
+        self.instance.get().cloned()
+    }
+
+    pub fn clone_lazy(&self) -> Self {
+        let instance = self.instance();
+        Self {
+            instance: Once::initialized(instance),
+            init: self.init,
+            id: self.id,
+            tags: self.tags,
+            state: Mutex::new(LazyState::Ready),
+            owner: AtomicUsize::new(NO_OWNER),
+        }
+    }
+
+    pub fn instance(&self) -> Arc<T> {
+        self._get().clone()
+    }
+
+    pub fn to_ref(&self) -> &T {
+        self._get().as_ref()
+    }
+}
@@ -0,0 +1,52 @@
+use alloc::string::String;
+use core::fmt::{self, Display, Formatter};
+use core::time::Duration;
+
+/// Initialization state of a [`Lazy`](crate::Lazy)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LazyState {
+    Uninit,
+    Initializing,
+    Ready,
+    /// Init panicked, with the panic message
+    Failed(String),
+}
+
+/// What a [`Lazy`](crate::Lazy) does on access after its init panicked
+///
+/// Without `std` feature init panic is always [`InitPolicy::FailFast`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InitPolicy {
+    /// Run init again on the next access
+    #[default]
+    Retry,
+    /// Panic with the original panic message
+    FailFast,
+}
+
+/// Error of [`Lazy::try_instance`](crate::Lazy::try_instance)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InitError {
+    /// Callback var didn't call `callback.call(value)` within `#[var(timeout)]`
+    Timeout(Duration),
+    /// Init panicked, for callback vars the panic of the async function
+    Panicked {
+        message: String,
+        location: Option<String>,
+    },
+    /// Callback var finished without calling `callback.call(value)`
+    NotCalled,
+}
+
+impl Display for InitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            InitError::Timeout(timeout) => write!(f, "Callback<T>.call(T) was not called within {timeout:?}"),
+            InitError::Panicked { message, location } => {
+                let location = location.as_deref().unwrap_or("unknown");
+                write!(f, "Init panicked at {location}: {message}")
+            }
+            InitError::NotCalled => write!(f, "Callback<T>.call(T) was not called"),
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use crate::inject::InjectStack;
+use crate::panics;
+use std::fmt::{self, Display, Formatter};
+use std::panic::Location;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// Failed refresh of a [`LazyTtl`], the last good value is kept
+#[derive(Debug, Clone)]
+pub struct RefreshError {
+    pub id: String,
+    pub message: String,
+    pub location: Option<String>,
+}
+
+impl Display for RefreshError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let location = self.location.as_deref().unwrap_or("unknown");
+        write!(f, "Refresh failed ({}) at {location}: {}", self.id, self.message)
+    }
+}
+
+type RefreshHook = Arc<dyn Fn(&RefreshError) + Send + Sync>;
+
+struct Entry<T> {
+    value: Arc<T>,
+    built: Instant,
+}
+
+/// Lazy value rebuilt after `ttl`, generated by `#[var(ttl = "5m")]`
+///
+/// Expired value is rebuilt on access. With [`with_refresh_ahead`](LazyTtl::with_refresh_ahead)
+/// it is rebuilt on a background thread shortly before it expires, readers keep getting the old value
+/// until `ttl`, after that the access rebuilds it. Failed background refresh is retried after `ahead / 4`.
+/// Panic in a refresh keeps the last good value and is reported to [`on_refresh_error`](LazyTtl::on_refresh_error) hooks.
+pub struct LazyTtl<T> {
+    entry: RwLock<Option<Entry<T>>>,
+    init: fn() -> T,
+    id: (&'static str, u32),
+    ttl: Duration,
+    refresh_ahead: Option<Duration>,
+    refreshing: AtomicBool,
+    retry_at: Mutex<Option<Instant>>,
+    building: Mutex<()>,
+    on_error: Mutex<Vec<RefreshHook>>,
+}
+
+impl<T: Send + Sync + 'static> LazyTtl<T> {
+    #[track_caller]
+    pub const fn new(init: fn() -> T, ttl: Duration) -> LazyTtl<T> {
+        let caller = Location::caller();
+        Self {
+            entry: RwLock::new(None),
+            init,
+            id: (caller.file(), caller.line()),
+            ttl,
+            refresh_ahead: None,
+            refreshing: AtomicBool::new(false),
+            retry_at: Mutex::new(None),
+            building: Mutex::new(()),
+            on_error: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Refresh in background once the value is older than `ttl - ahead`
+    pub const fn with_refresh_ahead(mut self, ahead: Duration) -> LazyTtl<T> {
+        self.refresh_ahead = Some(ahead);
+        self
+    }
+
+    pub(crate) fn id(&self) -> String {
+        format!("{}:{}", self.id.0, self.id.1)
+    }
+
+    fn _current(&self) -> Option<(Arc<T>, Duration)> {
+        let entry = self.entry.read().unwrap_or_else(|e| e.into_inner());
+        entry.as_ref().map(|entry| (entry.value.clone(), entry.built.elapsed()))
+    }
+
+    fn _store(&self, value: T) -> Arc<T> {
+        let value = Arc::new(value);
+        let entry = Entry {
+            value: value.clone(),
+            built: Instant::now(),
+        };
+        *self.entry.write().unwrap_or_else(|e| e.into_inner()) = Some(entry);
+        value
+    }
+
+    /// Current value, rebuilt when expired
+    pub fn get(&'static self) -> Arc<T> {
+        let Some((value, age)) = self._current() else {
+            return self._build_first();
+        };
+        if age >= self.ttl {
+            return self._refresh();
+        }
+        match self.refresh_ahead {
+            Some(ahead) if age + ahead >= self.ttl => {
+                self._refresh_background(ahead);
+                value
+            }
+            _ => value,
+        }
+    }
+
+    /// Value if built, never starts init or refresh
+    pub fn try_get(&self) -> Option<Arc<T>> {
+        self._current().map(|(value, _)| value)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self._current().map_or(true, |(_, age)| age >= self.ttl)
+    }
+
+    /// Hook called with every failed refresh
+    pub fn on_refresh_error(&self, hook: impl Fn(&RefreshError) + Send + Sync + 'static) {
+        self.on_error.lock().unwrap_or_else(|e| e.into_inner()).push(Arc::new(hook));
+    }
+
+    // Cycles are reported by `enter` before they could block on `building`
+    fn _build_first(&self) -> Arc<T> {
+        let _guard = InjectStack::enter(self.id());
+        let _building = self.building.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((value, _)) = self._current() {
+            return value;
+        }
+        self._store((self.init)())
+    }
+
+    fn _refresh(&self) -> Arc<T> {
+        let _guard = InjectStack::enter(self.id());
+        let _building = self.building.lock().unwrap_or_else(|e| e.into_inner());
+        match self._current() {
+            Some((value, age)) if age < self.ttl => value,
+            Some((value, _)) => self._try_build().unwrap_or(value),
+            None => self._store((self.init)()),
+        }
+    }
+
+    fn _refresh_background(&'static self, ahead: Duration) {
+        let _guard = InjectStack::enter(self.id());
+        let retry_at = *self.retry_at.lock().unwrap_or_else(|e| e.into_inner());
+        if retry_at.is_some_and(|retry_at| Instant::now() < retry_at) {
+            return;
+        }
+        if self.refreshing.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        std::thread::spawn(move || {
+            let _guard = InjectStack::enter(self.id());
+            let _building = self.building.lock().unwrap_or_else(|e| e.into_inner());
+            let failed = self._try_build().is_none();
+            *self.retry_at.lock().unwrap_or_else(|e| e.into_inner()) = failed.then(|| Instant::now() + ahead / 4);
+            self.refreshing.store(false, Ordering::Release);
+        });
+    }
+
+    // Keeps the last good value on panic
+    fn _try_build(&self) -> Option<Arc<T>> {
+        let result = panics::catch(self.init);
+        match result {
+            Ok(value) => Some(self._store(value)),
+            Err(caught) => {
+                let error = RefreshError {
+                    id: self.id(),
+                    message: caught.message,
+                    location: caught.location,
+                };
+                let hooks = self.on_error.lock().unwrap_or_else(|e| e.into_inner()).clone();
+                for hook in hooks {
+                    hook(&error);
+                }
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::inject::InjectStack;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::ops::Deref;
+use std::panic::Location;
+use std::sync::OnceLock;
+
+/// Lazy storing the value inline, without `Arc`
+///
+/// Not constructed again in [`InjectScope`](crate::InjectScope), use [`Lazy`](crate::Lazy) for services.
+pub struct LazyValue<T> {
+    value: OnceLock<T>,
+    init: fn() -> T,
+    id: (&'static str, u32),
+}
+
+impl<T> Deref for LazyValue<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self._get()
+    }
+}
+
+impl<T: Display> Display for LazyValue<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(inner) = self.value.get() {
+            return Display::fmt(inner, f);
+        };
+        Display::fmt("(Not initialized)", f)
+    }
+}
+
+impl<T: Debug> Debug for LazyValue<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(inner) = self.value.get() {
+            return Debug::fmt(inner, f);
+        };
+        Debug::fmt("(Not initialized) - use to_ref()", f)
+    }
+}
+
+impl<T> LazyValue<T> {
+    #[track_caller]
+    pub const fn new(init: fn() -> T) -> LazyValue<T> {
+        let caller = Location::caller();
+        Self {
+            value: OnceLock::new(),
+            init,
+            id: (caller.file(), caller.line()),
+        }
+    }
+
+    #[inline]
+    fn _get(&self) -> &T {
+        match self.value.get() {
+            Some(value) => value,
+            None => self._init(),
+        }
+    }
+
+    pub(crate) fn id(&self) -> String {
+        format!("{}:{}", self.id.0, self.id.1)
+    }
+
+    #[cold]
+    fn _init(&self) -> &T {
+        let _guard = InjectStack::enter(self.id());
+        self.value.get_or_init(self.init)
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.value.get().is_some()
+    }
+
+    pub fn try_get(&self) -> Option<&T> {
+        self.value.get()
+    }
+
+    /// `&'static T` for a static, without `Arc` clone
+    pub fn to_ref(&self) -> &T {
+        self._get()
+    }
+}
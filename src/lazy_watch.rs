@@ -0,0 +1,211 @@
+use crate::inject::InjectStack;
+use crate::Callback;
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::panic::Location;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+use std::task::{Context, Poll, Waker};
+
+/// Publisher of a [`LazyWatch`], prepared on the thread accessing the var
+#[doc(hidden)]
+pub type WatchStart<T> = Box<dyn FnOnce(Callback<T>) + Send>;
+
+struct WatchState<T> {
+    value: Option<Arc<T>>,
+    version: u64,
+    closed: bool,
+    wakers: Vec<Waker>,
+}
+
+struct WatchShared<T> {
+    state: Mutex<WatchState<T>>,
+    changed: Condvar,
+}
+
+impl<T> WatchShared<T> {
+    fn lock(&self) -> MutexGuard<'_, WatchState<T>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn notify(&self, mut state: MutexGuard<'_, WatchState<T>>) {
+        let wakers = std::mem::take(&mut state.wakers);
+        drop(state);
+        self.changed.notify_all();
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+/// Publishing side of [`LazyWatch`], closes the watch on drop
+pub(crate) struct WatchSink<T>(Arc<WatchShared<T>>);
+
+impl<T> WatchSink<T> {
+    pub(crate) fn publish(&self, value: T) {
+        let mut state = self.0.lock();
+        state.value = Some(Arc::new(value));
+        state.version += 1;
+        self.0.notify(state);
+    }
+}
+
+impl<T> Drop for WatchSink<T> {
+    fn drop(&mut self) {
+        let mut state = self.0.lock();
+        state.closed = true;
+        self.0.notify(state);
+    }
+}
+
+/// Var updated with every `callback.call(value)`, generated by `#[var(watch)]`
+///
+/// Publisher starts on a background thread on first access.
+pub struct LazyWatch<T> {
+    shared: OnceLock<Arc<WatchShared<T>>>,
+    start: fn() -> WatchStart<T>,
+    id: (&'static str, u32),
+}
+
+impl<T: Debug + Send + Sync + 'static> Debug for LazyWatch<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.try_get() {
+            Some(value) => Debug::fmt(&value, f),
+            None => Debug::fmt("(Not initialized) - use get()", f),
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static> LazyWatch<T> {
+    #[track_caller]
+    pub const fn new(start: fn() -> WatchStart<T>) -> LazyWatch<T> {
+        let caller = Location::caller();
+        Self {
+            shared: OnceLock::new(),
+            start,
+            id: (caller.file(), caller.line()),
+        }
+    }
+
+    pub(crate) fn id(&self) -> String {
+        format!("{}:{}", self.id.0, self.id.1)
+    }
+
+    fn _shared(&self) -> &Arc<WatchShared<T>> {
+        self.shared.get_or_init(|| {
+            let id = self.id();
+            let guard = InjectStack::enter(id.clone());
+            let publisher = (self.start)();
+            drop(guard);
+            let shared = Arc::new(WatchShared {
+                state: Mutex::new(WatchState {
+                    value: None,
+                    version: 0,
+                    closed: false,
+                    wakers: Vec::new(),
+                }),
+                changed: Condvar::new(),
+            });
+            let callback = Callback::watch(WatchSink(shared.clone()));
+            std::thread::Builder::new()
+                .name(format!("watch {id}"))
+                .spawn(move || publisher(callback))
+                .expect("watch publisher thread");
+            shared
+        })
+    }
+
+    /// Latest value, blocks until the first one is published
+    pub fn get(&self) -> Arc<T> {
+        let shared = self._shared();
+        let mut state = shared.lock();
+        loop {
+            if let Some(value) = &state.value {
+                return value.clone();
+            }
+            if state.closed {
+                panic!("\nCallback<T>.call(T) was not called\n")
+            }
+            state = shared.changed.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// Latest value, never starts the publisher
+    pub fn try_get(&self) -> Option<Arc<T>> {
+        self.shared.get()?.lock().value.clone()
+    }
+
+    /// Receiver of values published after this call
+    pub fn subscribe(&self) -> WatchReceiver<T> {
+        let shared = self._shared().clone();
+        let seen = shared.lock().version;
+        WatchReceiver { shared, seen }
+    }
+
+    /// Next published value, `None` once the publisher finished
+    pub async fn changed(&self) -> Option<Arc<T>> {
+        self.subscribe().changed().await
+    }
+}
+
+/// Subscription to a [`LazyWatch`]
+pub struct WatchReceiver<T> {
+    shared: Arc<WatchShared<T>>,
+    seen: u64,
+}
+
+impl<T> WatchReceiver<T> {
+    /// Latest value, marked as seen
+    pub fn latest(&mut self) -> Option<Arc<T>> {
+        let state = self.shared.lock();
+        self.seen = state.version;
+        state.value.clone()
+    }
+
+    pub fn has_changed(&self) -> bool {
+        self.shared.lock().version > self.seen
+    }
+
+    /// Waits for a value not seen yet, `None` once the publisher finished
+    pub fn changed(&mut self) -> Changed<'_, T> {
+        Changed { receiver: self }
+    }
+
+    /// Blocking [`changed`](WatchReceiver::changed)
+    pub fn wait_changed(&mut self) -> Option<Arc<T>> {
+        let mut state = self.shared.lock();
+        loop {
+            if state.version > self.seen {
+                self.seen = state.version;
+                return state.value.clone();
+            }
+            if state.closed {
+                return None;
+            }
+            state = self.shared.changed.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+}
+
+/// Future of [`WatchReceiver::changed`]
+pub struct Changed<'a, T> {
+    receiver: &'a mut WatchReceiver<T>,
+}
+
+impl<T> Future for Changed<'_, T> {
+    type Output = Option<Arc<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let receiver = &mut *self.get_mut().receiver;
+        let mut state = receiver.shared.lock();
+        if state.version > receiver.seen {
+            receiver.seen = state.version;
+            return Poll::Ready(state.value.clone());
+        }
+        if state.closed {
+            return Poll::Ready(None);
+        }
+        if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
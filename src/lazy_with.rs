@@ -0,0 +1,87 @@
+use crate::inject::OverrideGuard;
+use crate::{InitPolicy, Lazy, LazyState};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::ops::Deref;
+use std::panic::Location;
+use std::sync::{Arc, Mutex};
+
+/// Lazy with capturing initializer, the closure is dropped after it runs
+///
+/// Built once and shared by every [`InjectScope`](crate::InjectScope), overrides apply as for `Lazy`.
+/// Each instance has own id, also when built by one helper fn or in a loop.
+/// The closure can't run again, so a panicked init is [`InitPolicy::FailFast`]:
+/// later accesses panic with the original message.
+pub struct LazyWith<T, F = fn() -> T> {
+    lazy: Lazy<T>,
+    init: Mutex<Option<F>>,
+}
+
+impl<T: Send + Sync + 'static, F: FnOnce() -> T> Deref for LazyWith<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.to_ref()
+    }
+}
+
+impl<T: Display + Send + Sync + 'static, F> Display for LazyWith<T, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.lazy, f)
+    }
+}
+
+impl<T: Debug + Send + Sync + 'static, F> Debug for LazyWith<T, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.lazy, f)
+    }
+}
+
+impl<T, F> LazyWith<T, F> {
+    #[track_caller]
+    pub const fn new(init: F) -> LazyWith<T, F> {
+        let lazy: Lazy<T> = Lazy::build(|| unreachable!(), Location::caller(), true);
+        Self {
+            lazy: lazy.with_policy(InitPolicy::FailFast).numbered(),
+            init: Mutex::new(Some(init)),
+        }
+    }
+
+    pub(crate) fn id(&self) -> String {
+        self.lazy.id()
+    }
+}
+
+impl<T: Send + Sync + 'static, F: FnOnce() -> T> LazyWith<T, F> {
+    // Taken by the first init, FailFast reports its panic to every later access
+    fn _init(&self) -> impl FnOnce() -> T + '_ {
+        || {
+            let init = self.init.lock().unwrap_or_else(|e| e.into_inner()).take();
+            init.expect("LazyWith initializer already taken")()
+        }
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.lazy.is_initialized()
+    }
+
+    pub fn try_get(&self) -> Option<Arc<T>> {
+        self.lazy.try_get()
+    }
+
+    pub fn state(&self) -> LazyState {
+        self.lazy.state()
+    }
+
+    pub fn instance(&self) -> Arc<T> {
+        self.lazy.instance_with(self._init())
+    }
+
+    pub fn to_ref(&self) -> &T {
+        self.lazy.to_ref_with(self._init())
+    }
+
+    /// Replace instance on the current thread, until the guard is dropped
+    pub fn set_override(&self, value: impl Into<T>) -> OverrideGuard {
+        self.lazy.set_override(value)
+    }
+}
@@ -1,12 +1,52 @@
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+#[cfg(not(any(feature = "std", feature = "spin")))]
+compile_error!("wildbird needs `std` feature, or `spin` feature without `std`");
+
+#[cfg(feature = "std")]
 mod callback;
 mod inject;
+#[cfg(feature = "std")]
+mod lazy;
+#[cfg(not(feature = "std"))]
+#[path = "lazy_spin.rs"]
 mod lazy;
+mod lazy_state;
+#[cfg(feature = "std")]
+mod lazy_local;
+#[cfg(feature = "std")]
+mod lazy_mut;
+#[cfg(feature = "std")]
+mod lazy_ttl;
+#[cfg(feature = "std")]
+mod lazy_value;
+#[cfg(feature = "std")]
+mod lazy_watch;
+#[cfg(feature = "std")]
+mod lazy_with;
+#[cfg(feature = "std")]
+mod panics;
+#[cfg(feature = "std")]
+mod registry;
+mod secret;
+#[cfg(feature = "serde")]
+mod snapshot;
+#[cfg(feature = "std")]
+mod verify;
+#[cfg(feature = "std")]
+pub mod intercept;
+#[cfg(feature = "std")]
 pub mod tools;
 
-#[cfg(any(feature = "timed-log", feature = "timed-tracing", feature = "timed"))]
+#[cfg(any(feature = "timed", feature = "timed-log", feature = "timed-tracing", feature = "lazy-stats"))]
 pub mod metric;
+#[cfg(feature = "persist")]
+pub mod persist;
+#[cfg(feature = "std")]
+pub mod testing;
 #[cfg(feature = "rayon")]
 pub mod threads;
 #[cfg(feature = "rayon")]
@@ -18,9 +58,36 @@ pub mod prelude;
 /// Macro System
 pub extern crate wildbird_macro_derive as derive;
 
-pub use self::callback::Callback;
 pub use self::inject::Inject;
-pub use self::lazy::Lazy;
+pub use self::lazy::{set_context_id, Lazy};
+pub use self::lazy_state::{InitError, InitPolicy, LazyState};
+pub use self::secret::Secret;
+pub use crate::derive::verify_test;
+
+#[cfg(feature = "std")]
+pub use self::callback::Callback;
+#[cfg(feature = "std")]
+pub use self::inject::{InjectScope, OverrideGuard, ScopeGuard};
+#[cfg(feature = "std")]
+pub use self::lazy_local::LazyLocal;
+#[cfg(feature = "std")]
+pub use self::lazy_mut::{LazyMut, MutGuard, MutReadGuard};
+#[cfg(feature = "tokio")]
+pub use self::lazy_mut::{LazyMutAsync, MutAsyncGuard, MutAsyncReadGuard};
+#[cfg(feature = "std")]
+pub use self::lazy_ttl::{LazyTtl, RefreshError};
+#[cfg(feature = "std")]
+pub use self::lazy_value::LazyValue;
+#[cfg(feature = "std")]
+pub use self::lazy_watch::{Changed, LazyWatch, WatchReceiver};
+#[cfg(feature = "std")]
+pub use self::lazy_with::LazyWith;
+#[cfg(feature = "std")]
+pub use self::registry::{find_by_tag, register, registered, Registered, Tagged};
+#[cfg(feature = "serde")]
+pub use self::snapshot::{register_var, snapshot_vars, REDACTED};
+#[cfg(feature = "std")]
+pub use self::verify::{verify, InitPanic, Unresolved, Verify, VerifyReport};
 
 /// Private Module
 /// > **Don't use in your code directly**
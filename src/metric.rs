@@ -1,3 +1,11 @@
+#[cfg(feature = "lazy-stats")]
+use std::fmt::{self, Display, Formatter};
+#[cfg(feature = "lazy-stats")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "lazy-stats")]
+use std::sync::{Arc, Mutex, OnceLock};
+#[cfg(feature = "lazy-stats")]
+use std::time::{Duration, SystemTime};
 
 #[cfg(all(feature = "timed-log", not(feature = "timed-tracing")))]
 pub use log::info as _print_timed;
@@ -43,6 +51,129 @@ macro_rules! timed_return {
     }
 pub use timed_return;
 
+/// Interceptor logging method duration with `timed!` backend
+///
+/// ```ignore
+/// #[intercept(with = wildbird::metric::Timed)]
+/// impl HelloService { /* ... */ }
+/// ```
+#[cfg(any(feature = "timed-log", feature = "timed-tracing"))]
+#[derive(Default)]
+pub struct Timed;
+
+#[cfg(any(feature = "timed-log", feature = "timed-tracing"))]
+impl crate::intercept::Interceptor for Timed {
+    fn after(&self, call: &crate::intercept::Call) {
+        _print_timed!("{}: took {}ms", call, call.elapsed().as_millis());
+    }
+
+    fn on_error(&self, call: &crate::intercept::Call, error: &dyn std::fmt::Debug) {
+        _print_timed!("{}: failed after {}ms: {:?}", call, call.elapsed().as_millis(), error);
+    }
+}
+
+/// Init and access metrics of a [`Lazy`](crate::Lazy), see [`Lazy::stats`](crate::Lazy::stats)
+#[cfg(feature = "lazy-stats")]
+#[derive(Debug, Clone)]
+pub struct LazyStats {
+    pub id: String,
+    pub type_name: &'static str,
+    pub first_access: Option<SystemTime>,
+    /// `None` until the global instance is constructed
+    pub init_duration: Option<Duration>,
+    /// Name (or id) of the thread that constructed the instance
+    pub built_by: Option<String>,
+    /// Counted with `timed-access` feature only
+    pub accesses: Option<u64>,
+}
+
+#[cfg(feature = "lazy-stats")]
+impl Display for LazyStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.type_name, self.id)?;
+        match (self.init_duration, &self.built_by) {
+            (Some(duration), Some(thread)) => write!(f, ": init took {}ms on {thread}", duration.as_millis())?,
+            _ => write!(f, ": not initialized")?,
+        }
+        if let Some(accesses) = self.accesses {
+            write!(f, ", {accesses} accesses")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "lazy-stats")]
+pub(crate) struct StatsRecord {
+    id: String,
+    type_name: &'static str,
+    first_access: SystemTime,
+    init: OnceLock<(Duration, String)>,
+    accesses: AtomicU64,
+}
+
+#[cfg(feature = "lazy-stats")]
+static RECORDS: Mutex<Vec<Arc<StatsRecord>>> = Mutex::new(Vec::new());
+
+#[cfg(feature = "lazy-stats")]
+impl StatsRecord {
+    /// New record, listed by [`lazy_stats`]
+    pub(crate) fn register(id: String, type_name: &'static str) -> Arc<StatsRecord> {
+        let record = Arc::new(StatsRecord {
+            id,
+            type_name,
+            first_access: SystemTime::now(),
+            init: OnceLock::new(),
+            accesses: AtomicU64::new(0),
+        });
+        RECORDS.lock().unwrap_or_else(|e| e.into_inner()).push(record.clone());
+        record
+    }
+
+    #[inline]
+    pub(crate) fn access(&self) {
+        #[cfg(feature = "timed-access")]
+        self.accesses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn initialized(&self, duration: Duration) {
+        let thread = std::thread::current();
+        let thread = match thread.name() {
+            Some(name) => name.to_string(),
+            None => format!("{:?}", thread.id()),
+        };
+        let _ = self.init.set((duration, thread));
+    }
+
+    pub(crate) fn stats(&self) -> LazyStats {
+        let init = self.init.get();
+        LazyStats {
+            id: self.id.clone(),
+            type_name: self.type_name,
+            first_access: Some(self.first_access),
+            init_duration: init.map(|(duration, _)| *duration),
+            built_by: init.map(|(_, thread)| thread.clone()),
+            accesses: cfg!(feature = "timed-access").then(|| self.accesses.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// Stats of every accessed `Lazy`, slowest init first
+#[cfg(feature = "lazy-stats")]
+pub fn lazy_stats() -> Vec<LazyStats> {
+    let records = RECORDS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut stats: Vec<LazyStats> = records.iter().map(|record| record.stats()).collect();
+    stats.sort_by_key(|stats| std::cmp::Reverse(stats.init_duration));
+    stats
+}
+
+/// Logs [`lazy_stats`] with `timed!` backend
+#[cfg(all(feature = "lazy-stats", any(feature = "timed-log", feature = "timed-tracing")))]
+pub fn dump_lazy_stats() {
+    for stats in lazy_stats() {
+        _print_timed!("{}", stats);
+    }
+}
+
 #[cfg(all(feature = "timed-log", test))]
 mod timed_test_log {
     use crate::metric::*;
@@ -84,6 +215,8 @@ mod timed_test_log {
             wait(3);
             3
         };
+        #[cfg(feature = "lazy-stats")]
+        dump_lazy_stats();
     }
 }
 
@@ -125,5 +258,7 @@ mod timed_test_tracing {
             wait(3);
             3
         };
+        #[cfg(feature = "lazy-stats")]
+        dump_lazy_stats();
     }
 }
@@ -0,0 +1,106 @@
+use crate::inject::InjectStack;
+use crate::InitError;
+use std::any::Any;
+use std::cell::RefCell;
+#[allow(deprecated)]
+use std::panic::{self, AssertUnwindSafe, PanicInfo};
+use std::sync::{Arc, Mutex};
+
+/// Panic caught while constructing a `Lazy`
+#[derive(Debug, Clone)]
+pub(crate) struct CaughtPanic {
+    pub message: String,
+    pub location: Option<String>,
+    /// Ids under construction when panicked, innermost last
+    pub stack: Vec<String>,
+    /// Ids of the detected circular dependency, repeated id last
+    pub cycle: Option<Vec<String>>,
+}
+
+thread_local! {
+    static LAST_PANIC: RefCell<Option<CaughtPanic>> = const { RefCell::new(None) };
+    static RAISED: RefCell<Option<InitError>> = const { RefCell::new(None) };
+}
+
+#[allow(deprecated)]
+type PanicHook = dyn Fn(&PanicInfo<'_>) + Sync + Send + 'static;
+
+// Hook users and the hook replaced by the first one
+static HOOK: Mutex<(usize, Option<Arc<PanicHook>>)> = Mutex::new((0, None));
+
+pub(crate) fn message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        return message.to_string();
+    }
+    if let Some(message) = payload.downcast_ref::<String>() {
+        return message.clone();
+    }
+    "Box<dyn Any>".to_string()
+}
+
+/// Previous panic hook is restored when the last guard drops
+pub(crate) struct HookGuard {}
+
+// Records panic details on the panicking thread, then runs the previous hook
+pub(crate) fn install_hook() -> HookGuard {
+    let mut hook = HOOK.lock().unwrap_or_else(|e| e.into_inner());
+    hook.0 += 1;
+    if hook.0 == 1 {
+        let previous: Arc<PanicHook> = Arc::from(panic::take_hook());
+        hook.1 = Some(previous.clone());
+        panic::set_hook(Box::new(move |info| {
+            let caught = CaughtPanic {
+                message: message(info.payload()),
+                location: info.location().map(|l| l.to_string()),
+                stack: InjectStack::snapshot(),
+                cycle: None,
+            };
+            let _ = LAST_PANIC.try_with(|last| last.replace(Some(caught)));
+            previous(info)
+        }));
+    }
+    HookGuard {}
+}
+
+impl Drop for HookGuard {
+    fn drop(&mut self) {
+        let mut hook = HOOK.lock().unwrap_or_else(|e| e.into_inner());
+        hook.0 -= 1;
+        // Hook can't be replaced while panicking, kept until the next install
+        if hook.0 > 0 || std::thread::panicking() {
+            return;
+        }
+        if let Some(previous) = hook.1.take() {
+            drop(panic::take_hook());
+            panic::set_hook(Box::new(move |info| previous(info)));
+        }
+    }
+}
+
+/// Panics with the error message, [`take_raised`] returns the typed error
+pub(crate) fn raise(error: InitError) -> ! {
+    let message = error.to_string();
+    RAISED.with(|raised| raised.replace(Some(error)));
+    panic!("\n{message}\n")
+}
+
+pub(crate) fn take_raised() -> Option<InitError> {
+    RAISED.with(|raised| raised.take())
+}
+
+pub(crate) fn catch<R>(f: impl FnOnce() -> R) -> Result<R, CaughtPanic> {
+    let _hook = install_hook();
+    LAST_PANIC.with(|last| last.take());
+    RAISED.with(|raised| raised.take());
+    InjectStack::take_cycle();
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        let mut caught = LAST_PANIC.with(|last| last.take()).unwrap_or_else(|| CaughtPanic {
+            message: message(payload.as_ref()),
+            location: None,
+            stack: vec![],
+            cycle: None,
+        });
+        caught.cycle = InjectStack::take_cycle();
+        caught
+    })
+}
@@ -0,0 +1,99 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+/// Cache directory of `#[var(persist)]` for the package, `None` when no per-user dir is known
+///
+/// `WILDBIRD_CACHE_DIR`, `$XDG_CACHE_HOME/wildbird`, `~/.cache/wildbird` or `%LOCALAPPDATA%\wildbird`,
+/// with the package name appended, binaries don't share cache files.
+/// Nothing is cached without the dir, the shared temp dir would let other users plant values.
+pub fn cache_dir(package: &str) -> Option<PathBuf> {
+    let env = |name| std::env::var_os(name).filter(|dir| !dir.is_empty()).map(PathBuf::from);
+    let base = match env("WILDBIRD_CACHE_DIR") {
+        Some(dir) => dir,
+        None => env("XDG_CACHE_HOME")
+            .or_else(|| env("HOME").map(|home| home.join(".cache")))
+            .or_else(|| env("LOCALAPPDATA"))?
+            .join("wildbird"),
+    };
+    Some(base.join(package))
+}
+
+/// Cache file of the key in [`cache_dir`]
+pub fn cache_path(package: &str, key: &str) -> Option<PathBuf> {
+    cache_dir(package).map(|dir| cache_path_in(&dir, key))
+}
+
+/// Cache file of the key in `dir`
+///
+/// Panics on absolute keys or keys with `..`, `#[var(persist)]` rejects them at compile time.
+pub fn cache_path_in(dir: &Path, key: &str) -> PathBuf {
+    let relative = Path::new(key).components().all(|component| matches!(component, Component::Normal(_)));
+    assert!(relative, "Invalid persist key: {key}, expected relative path without \"..\"");
+    let mut path = dir.join(key);
+    if path.extension().is_none() {
+        path.set_extension("json");
+    }
+    path
+}
+
+#[derive(Serialize)]
+struct Entry<'a, T> {
+    key: &'a str,
+    version: u32,
+    value: &'a T,
+}
+
+#[derive(Deserialize)]
+struct LoadedEntry<T> {
+    key: String,
+    version: u32,
+    value: T,
+}
+
+fn load<T: DeserializeOwned>(path: &Path, key: &str, version: u32) -> Option<T> {
+    let file = std::fs::File::open(path).ok()?;
+    let entry: LoadedEntry<T> = serde_json::from_reader(std::io::BufReader::new(file)).ok()?;
+    (entry.key == key && entry.version == version).then_some(entry.value)
+}
+
+// Readable only by the owner, the cached values are trusted on load
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)
+}
+
+// Written to a temporary file first, readers never see a partial file
+fn store<T: Serialize>(path: &Path, key: &str, version: u32, value: &T) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        create_private_dir(dir)?;
+    }
+    let json = serde_json::to_vec(&Entry { key, version, value })?;
+    let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+    std::fs::write(&temp, json)?;
+    std::fs::rename(&temp, path)
+}
+
+/// Cached value when key and version match, otherwise computed and cached.
+/// Missing, corrupt or outdated file is recomputed, failed write only skips caching.
+#[doc(hidden)]
+pub fn load_or_compute<T: Serialize + DeserializeOwned>(package: &str, key: &str, version: u32, compute: fn() -> T) -> T {
+    match cache_dir(package) {
+        Some(dir) => load_or_compute_in(&dir, key, version, compute),
+        None => compute(),
+    }
+}
+
+/// [`load_or_compute`] with the cache in `dir`
+pub fn load_or_compute_in<T: Serialize + DeserializeOwned>(dir: &Path, key: &str, version: u32, compute: fn() -> T) -> T {
+    let path = cache_path_in(dir, key);
+    if let Some(value) = load(&path, key, version) {
+        return value;
+    }
+    let value = compute();
+    let _ = store(&path, key, version, &value);
+    value
+}
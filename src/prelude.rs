@@ -1,9 +1,29 @@
 pub use crate::derive::*;
-pub use crate::tools::prelude::*;
-pub use crate::Callback;
 pub use crate::Lazy;
 pub use crate::inject::Inject;
-pub use std::sync::Arc;
+pub use crate::Secret;
+pub use alloc::sync::Arc;
+
+#[cfg(feature = "std")]
+pub use crate::tools::prelude::*;
+#[cfg(feature = "std")]
+pub use crate::Callback;
+#[cfg(feature = "std")]
+pub use crate::LazyLocal;
+#[cfg(feature = "std")]
+pub use crate::LazyMut;
+#[cfg(feature = "tokio")]
+pub use crate::LazyMutAsync;
+#[cfg(feature = "std")]
+pub use crate::LazyTtl;
+#[cfg(feature = "std")]
+pub use crate::LazyValue;
+#[cfg(feature = "std")]
+pub use crate::LazyWatch;
+#[cfg(feature = "std")]
+pub use crate::LazyWith;
+#[cfg(feature = "std")]
+pub use crate::intercept::Interceptor;
 
 #[cfg(feature = "rayon")]
 pub use crate::threads::{
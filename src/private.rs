@@ -1,10 +1,22 @@
-use crate::{Callback, Lazy, Service};
-use std::future::Future;
+use crate::{Lazy, Service};
+#[cfg(feature = "std")]
+use crate::{Callback, LazyLocal, LazyMut, LazyTtl, LazyValue, LazyWatch};
+#[cfg(feature = "std")]
+pub use crate::lazy_local::LocalSlot;
+#[cfg(feature = "std")]
+pub use crate::lazy_watch::WatchStart;
+#[cfg(feature = "std")]
+use core::future::Future;
 
-#[cfg(not(feature = "tokio"))]
+#[doc(hidden)]
+pub use alloc::sync::Arc;
+
+#[cfg(all(feature = "std", not(feature = "tokio")))]
 use std::thread::spawn as spawn_blocking;
 #[cfg(feature = "tokio")]
-use tokio::task::spawn_blocking;
+fn spawn_blocking(f: impl FnOnce() + Send + 'static) {
+    BLOCK_RUNTIME.handle().spawn_blocking(f);
+}
 
 #[inline]
 #[track_caller]
@@ -13,6 +25,13 @@ pub const fn service_construct<S: Service>() -> Lazy<S::Service> {
     Lazy::new(|| S::construct())
 }
 
+#[inline]
+#[track_caller]
+#[doc(hidden)]
+pub const fn configuration_construct<C: Default>() -> Lazy<C> {
+    Lazy::new(C::default)
+}
+
 #[inline]
 #[track_caller]
 #[doc(hidden)]
@@ -23,22 +42,132 @@ pub const fn lazy_construct<T>(value: fn() -> T) -> Lazy<T> {
 #[inline]
 #[track_caller]
 #[doc(hidden)]
-pub fn block_callback<T, F, O>(future: fn(Callback<T>) -> F) -> T
+#[cfg(feature = "std")]
+pub const fn value_construct<T>(value: fn() -> T) -> LazyValue<T> {
+    LazyValue::new(value)
+}
+
+#[inline]
+#[track_caller]
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub const fn mut_construct<T>(value: fn() -> T) -> LazyMut<T> {
+    LazyMut::new(value)
+}
+
+#[inline]
+#[track_caller]
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub const fn local_construct<T>(slot: &'static std::thread::LocalKey<LocalSlot<T>>, value: fn() -> T) -> LazyLocal<T> {
+    LazyLocal::new(slot, value)
+}
+
+#[inline]
+#[track_caller]
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub const fn ttl_construct<T: Send + Sync + 'static>(value: fn() -> T, ttl: core::time::Duration) -> LazyTtl<T> {
+    LazyTtl::new(value, ttl)
+}
+
+#[inline]
+#[track_caller]
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub const fn watch_construct<T: Send + Sync + 'static>(start: fn() -> WatchStart<T>) -> LazyWatch<T> {
+    LazyWatch::new(start)
+}
+
+/// Publisher thread of a callback var, kept across accesses when it times out
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub struct Publisher<T> {
+    in_flight: std::sync::Mutex<Option<InFlight<T>>>,
+}
+
+#[cfg(feature = "std")]
+struct InFlight<T> {
+    rx: std::sync::mpsc::Receiver<T>,
+    panicked: Arc<std::sync::Mutex<Option<crate::panics::CaughtPanic>>>,
+}
+
+#[cfg(feature = "std")]
+impl<T> Publisher<T> {
+    pub const fn new() -> Publisher<T> {
+        Publisher {
+            in_flight: std::sync::Mutex::new(None),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Default for Publisher<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[inline]
+#[track_caller]
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub fn block_callback<T, F, O>(
+    publisher: &Publisher<T>,
+    future: impl FnOnce(Callback<T>) -> F + Send + 'static,
+    timeout: Option<core::time::Duration>,
+) -> T
 where
-    T: Send + 'static,
+    T: Send + Sync + 'static,
     F: Future<Output = O> + 'static,
 {
-    let (tx, rx) = std::sync::mpsc::sync_channel(1);
-    let callback = Callback::new(tx);
-    spawn_blocking(move || {
-        block(future(callback));
+    use crate::{panics, InitError};
+    use std::sync::mpsc::RecvTimeoutError;
+
+    let mut in_flight = publisher.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+    // Publisher that timed out before is awaited again, never spawned twice
+    let flight = in_flight.take().unwrap_or_else(|| {
+        let (tx, rx) = std::sync::mpsc::sync_channel(1);
+        let callback = Callback::new(tx.clone());
+        let panicked = Arc::new(std::sync::Mutex::new(None));
+        let publisher_panic = panicked.clone();
+        spawn_blocking(move || {
+            // Keeps the channel open until the panic is recorded
+            let _tx = tx;
+            if let Err(caught) = panics::catch(|| block(future(callback))) {
+                *publisher_panic.lock().unwrap_or_else(|e| e.into_inner()) = Some(caught);
+            }
+        });
+        InFlight { rx, panicked }
     });
-    rx.recv().expect("\nCallback<T>.call(T) was not called\n")
+    let received = match timeout {
+        Some(timeout) => flight.rx.recv_timeout(timeout),
+        None => flight.rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+    };
+    match received {
+        Ok(value) => value,
+        Err(RecvTimeoutError::Timeout) => {
+            *in_flight = Some(flight);
+            drop(in_flight);
+            panics::raise(InitError::Timeout(timeout.unwrap_or_default()))
+        }
+        Err(RecvTimeoutError::Disconnected) => {
+            drop(in_flight);
+            let caught = flight.panicked.lock().unwrap_or_else(|e| e.into_inner()).take();
+            panics::raise(match caught {
+                Some(caught) => InitError::Panicked {
+                    message: caught.message,
+                    location: caught.location,
+                },
+                None => InitError::NotCalled,
+            })
+        }
+    }
 }
 
 #[doc(hidden)]
 #[cfg(feature = "tokio")]
-static BLOCK_RUNTIME: Lazy<tokio::runtime::Runtime> = Lazy::new(|| {
+static BLOCK_RUNTIME: Lazy<tokio::runtime::Runtime> = Lazy::shared(|| {
     tokio::runtime::Builder::new_multi_thread()
         .enable_all()
         .worker_threads(2)
@@ -49,6 +178,7 @@ static BLOCK_RUNTIME: Lazy<tokio::runtime::Runtime> = Lazy::new(|| {
 
 #[inline(always)]
 #[doc(hidden)]
+#[cfg(feature = "std")]
 // Panic in non multi-thread tokio runtime
 pub fn block<T>(future: impl Future<Output = T>) -> T {
     #[cfg(feature = "tokio")]
@@ -61,12 +191,233 @@ pub fn block<T>(future: impl Future<Output = T>) -> T {
     }
 }
 
+#[doc(hidden)]
+#[cfg(feature = "tokio")]
+// Runs `#[wildbird::testing::test]` body, worker threads share the current InjectScope
+pub fn block_test<T>(future: impl Future<Output = T>, multi_thread: bool, worker_threads: usize) -> T {
+    let mut builder = match multi_thread {
+        true => tokio::runtime::Builder::new_multi_thread(),
+        false => tokio::runtime::Builder::new_current_thread(),
+    };
+    if multi_thread {
+        builder.worker_threads(worker_threads);
+    }
+    if let Some(scope) = crate::InjectScope::current() {
+        builder
+            .on_thread_start(move || {
+                crate::InjectScope::enter(scope.clone());
+            })
+            .on_thread_stop(|| crate::InjectScope::exit(None));
+    }
+    builder
+        .enable_all()
+        .build()
+        .expect("tokio test runtime")
+        .block_on(future)
+}
+
+#[doc(hidden)]
+#[cfg(all(feature = "std", not(feature = "tokio")))]
+pub fn block_test<T>(future: impl Future<Output = T>, _multi_thread: bool, _worker_threads: usize) -> T {
+    futures_lite::future::block_on(future)
+}
+
 #[inline(always)]
 #[doc(hidden)]
+#[cfg(feature = "std")]
 pub fn block_fn<D, F: Future<Output = D>>(future: fn() -> F) -> D {
     block(future())
 }
 
-pub trait PrivateService<T: 'static> {
-    fn lazy() -> &'static Lazy<T>;
-}
\ No newline at end of file
+/// `M` marks the item that declared the binding (the service itself or a `#[configuration]` type),
+/// so bindings for foreign types stay within the orphan rules.
+/// `T` is the service type, or a marker local to the `#[var(no_arc)]` resolving to `&'static T`.
+pub trait PrivateService<T: 'static, M = T>: Sized {
+    fn inject() -> Self;
+}
+
+/// Injector of generated code, also resolves `#[configuration]` services
+#[inline]
+pub fn inject<T: 'static, R, M>() -> R
+where
+    R: PrivateService<T, M> + 'static,
+{
+    R::inject()
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "testing")]
+macro_rules! __testing_only {
+    ($($item:item)*) => { $($item)* };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "testing"))]
+macro_rules! __testing_only {
+    ($($item:item)*) => {};
+}
+pub use __testing_only as testing_only;
+
+#[cfg(feature = "auto-register")]
+pub use inventory;
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "auto-register")]
+macro_rules! __auto_register {
+    ($lazy:path) => {
+        $crate::private::inventory::submit! { $crate::private::AutoRegister(&$lazy) }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "auto-register"))]
+macro_rules! __auto_register {
+    ($lazy:path) => {};
+}
+pub use __auto_register as auto_register;
+
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub use crate::registry::AutoRegister;
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "persist")]
+macro_rules! __persisted {
+    ($key:literal, $version:literal, $compute:path) => {
+        $crate::persist::load_or_compute(::core::env!("CARGO_PKG_NAME"), $key, $version, $compute)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "persist"))]
+macro_rules! __persisted {
+    ($key:literal, $version:literal, $compute:path) => {
+        ::core::compile_error!("#[var(persist)] requires wildbird `persist` feature")
+    };
+}
+pub use __persisted as persisted;
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "tokio")]
+macro_rules! __mut_async {
+    ($vis:vis static $name:ident: $ty:ty = $init:path, $label:literal, $secret:literal) => {
+        #[allow(non_upper_case_globals)]
+        $vis static $name: $crate::LazyMutAsync<$ty> = $crate::LazyMutAsync::new($init);
+        $crate::private::register_var!($name: $crate::LazyMutAsync<$ty>, $label, $secret);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "tokio"))]
+macro_rules! __mut_async {
+    ($vis:vis static $name:ident: $ty:ty = $init:path, $label:literal, $secret:literal) => {
+        ::core::compile_error!("#[var(mutable = \"async\")] requires wildbird `tokio` feature")
+    };
+}
+pub use __mut_async as mut_async;
+
+#[doc(hidden)]
+#[cfg(feature = "serde")]
+pub use crate::snapshot::{RegisteredVar, SnapshotSerialize, SnapshotUnsupported};
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(all(feature = "serde", feature = "auto-register"))]
+macro_rules! __register_var {
+    ($lazy:path: $ty:ty, $name:literal, $secret:literal) => {
+        const _: () = {
+            static VAR: $crate::private::RegisteredVar<$ty> = $crate::private::RegisteredVar {
+                lazy: &$lazy,
+                name: $name,
+                secret: $secret,
+                snapshot: |lazy| {
+                    use $crate::private::{SnapshotSerialize, SnapshotUnsupported};
+                    (&$crate::private::Probe(lazy)).snapshot()
+                },
+            };
+            $crate::private::inventory::submit! { $crate::private::AutoRegister(&VAR) }
+        };
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(all(feature = "serde", feature = "auto-register")))]
+macro_rules! __register_var {
+    ($lazy:path: $ty:ty, $name:literal, $secret:literal) => {
+        $crate::private::auto_register!($lazy);
+    };
+}
+pub use __register_var as register_var;
+
+/// Auto-ref specialization, `(&Probe(value)).method()` resolves the trait impl on `Probe<T>`
+/// when its bounds hold, otherwise the fallback impl on `&Probe<T>`
+#[doc(hidden)]
+pub struct Probe<T>(pub T);
+
+#[doc(hidden)]
+#[cfg(feature = "secret")]
+pub use crate::secret::{WipeUnsupported, WipeZeroize};
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "secret")]
+macro_rules! __secret {
+    ($ty:ty, $value:expr) => {{
+        use $crate::private::{WipeUnsupported, WipeZeroize};
+        let wipe = (&$crate::private::Probe(::core::marker::PhantomData::<$ty>)).wipe();
+        $crate::Secret::with_wipe($value, wipe)
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "secret"))]
+macro_rules! __secret {
+    ($ty:ty, $value:expr) => {
+        $crate::Secret::without_zeroize($value)
+    };
+}
+pub use __secret as secret;
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "std")]
+macro_rules! __secret_file {
+    ($path:expr, $compute:path) => {
+        $crate::private::secret_or_file($path, $compute)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "std"))]
+macro_rules! __secret_file {
+    ($path:expr, $compute:path) => {
+        ::core::compile_error!("#[var(secret_file)] requires wildbird `std` feature")
+    };
+}
+pub use __secret_file as secret_file;
+
+/// Parses `#[var(secret_file)]`, falls back to the var function when the file is missing
+#[track_caller]
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub fn secret_or_file<T: core::str::FromStr>(path: &str, compute: fn() -> T) -> T {
+    match crate::Secret::from_file(path) {
+        Ok(secret) => secret
+            .expose()
+            .parse()
+            .unwrap_or_else(|_| panic!("Invalid secret file: {path}")),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => compute(),
+        Err(e) => panic!("Secret file {path}: {e}"),
+    }
+}
@@ -0,0 +1,244 @@
+use crate::{Lazy, LazyLocal, LazyMut, LazyTtl, LazyValue, LazyWatch, LazyWith};
+use std::any::{type_name, Any};
+use std::sync::{Arc, Mutex};
+
+/// Type erased `Lazy` in the service registry
+///
+/// Every `#[var]` kind is registered. Vars without an `Arc` instance resolve to a reference:
+/// `#[var(no_arc)]` to `&'static T`, `thread_local` and `mutable` vars to the static itself
+/// (`&'static LazyLocal<T>`, `&'static LazyMut<T>`), see [`Tagged::downcast`].
+pub trait Registered: Sync {
+    /// Declaration site `file:line`
+    fn id(&self) -> String;
+
+    fn type_name(&self) -> &'static str;
+
+    fn tags(&self) -> &'static [&'static str];
+
+    /// Resolves instance, in the current [`InjectScope`](crate::InjectScope) if any
+    fn resolve(&'static self) -> Arc<dyn Any + Send + Sync>;
+
+    /// Var name and current value for [`snapshot_vars`](crate::snapshot_vars), `None` for services
+    #[cfg(feature = "serde")]
+    fn snapshot(&self) -> Option<(&'static str, serde_json::Value)> {
+        None
+    }
+}
+
+impl<T: Send + Sync + 'static> Registered for Lazy<T> {
+    fn id(&self) -> String {
+        Lazy::id(self)
+    }
+
+    fn type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        Lazy::tags(self)
+    }
+
+    fn resolve(&'static self) -> Arc<dyn Any + Send + Sync> {
+        self.instance()
+    }
+}
+
+impl<T: Send + Sync + 'static, F: FnOnce() -> T + Send> Registered for LazyWith<T, F> {
+    fn id(&self) -> String {
+        LazyWith::id(self)
+    }
+
+    fn type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn resolve(&'static self) -> Arc<dyn Any + Send + Sync> {
+        self.instance()
+    }
+}
+
+impl<T: Send + Sync + 'static> Registered for LazyValue<T> {
+    fn id(&self) -> String {
+        LazyValue::id(self)
+    }
+
+    fn type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn resolve(&'static self) -> Arc<dyn Any + Send + Sync> {
+        Arc::new(self.to_ref())
+    }
+}
+
+impl<T: Send + Sync + 'static> Registered for LazyTtl<T> {
+    fn id(&self) -> String {
+        LazyTtl::id(self)
+    }
+
+    fn type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn resolve(&'static self) -> Arc<dyn Any + Send + Sync> {
+        self.get()
+    }
+}
+
+impl<T: Send + Sync + 'static> Registered for LazyWatch<T> {
+    fn id(&self) -> String {
+        LazyWatch::id(self)
+    }
+
+    fn type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn resolve(&'static self) -> Arc<dyn Any + Send + Sync> {
+        self.get()
+    }
+}
+
+// Built on the current thread
+impl<T: 'static> Registered for LazyLocal<T> {
+    fn id(&self) -> String {
+        LazyLocal::id(self)
+    }
+
+    fn type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn resolve(&'static self) -> Arc<dyn Any + Send + Sync> {
+        self.with(|_| ());
+        Arc::new(self)
+    }
+}
+
+impl<T: Send + Sync + 'static> Registered for LazyMut<T> {
+    fn id(&self) -> String {
+        LazyMut::id(self)
+    }
+
+    fn type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn resolve(&'static self) -> Arc<dyn Any + Send + Sync> {
+        drop(self.read());
+        Arc::new(self)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T: Send + Sync + 'static> Registered for crate::LazyMutAsync<T> {
+    fn id(&self) -> String {
+        crate::LazyMutAsync::id(self)
+    }
+
+    fn type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn resolve(&'static self) -> Arc<dyn Any + Send + Sync> {
+        self._init();
+        Arc::new(self)
+    }
+}
+
+static REGISTRY: Mutex<Vec<&'static dyn Registered>> = Mutex::new(Vec::new());
+
+/// Link time registration, submitted by macros with `auto-register` feature
+#[doc(hidden)]
+pub struct AutoRegister(pub &'static dyn Registered);
+
+#[cfg(feature = "auto-register")]
+inventory::collect!(AutoRegister);
+
+/// Adds service or var to the registry checked by [`verify`](crate::verify)
+pub fn register(lazy: &'static dyn Registered) {
+    let mut registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    let id = lazy.id();
+    if !registry.iter().any(|entry| entry.id() == id) {
+        registry.push(lazy);
+    }
+}
+
+/// Registered services, in registration order
+///
+/// With `auto-register` feature, every `#[service]`, `#[configuration]` and `#[var]`
+/// linked into the binary comes first.
+pub fn registered() -> Vec<&'static dyn Registered> {
+    let manual = REGISTRY.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    #[cfg(feature = "auto-register")]
+    {
+        let mut all: Vec<&'static dyn Registered> = inventory::iter::<AutoRegister>
+            .into_iter()
+            .map(|entry| entry.0)
+            .collect();
+        for entry in manual {
+            if !all.iter().any(|e| e.id() == entry.id()) {
+                all.push(entry);
+            }
+        }
+        all
+    }
+    #[cfg(not(feature = "auto-register"))]
+    manual
+}
+
+/// Resolved service found by tag
+#[derive(Clone)]
+pub struct Tagged {
+    pub type_name: &'static str,
+    pub id: String,
+    pub instance: Arc<dyn Any + Send + Sync>,
+}
+
+impl Tagged {
+    /// `Arc<T>` of the instance, `Arc<&'static T>` for vars resolving to a reference
+    pub fn downcast<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.instance.clone().downcast().ok()
+    }
+}
+
+/// Resolves every registered service with the tag, in registration order
+pub fn find_by_tag(tag: &str) -> Vec<Tagged> {
+    registered()
+        .into_iter()
+        .filter(|entry| entry.tags().contains(&tag))
+        .map(|entry| Tagged {
+            type_name: entry.type_name(),
+            id: entry.id(),
+            instance: entry.resolve(),
+        })
+        .collect()
+}
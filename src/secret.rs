@@ -0,0 +1,109 @@
+use core::fmt::{self, Debug, Display, Formatter};
+#[cfg(feature = "secret")]
+use crate::private::Probe;
+#[cfg(feature = "secret")]
+use core::marker::PhantomData;
+#[cfg(feature = "secret")]
+use zeroize::Zeroize;
+
+/// Value hidden from `Display` and `Debug`, zeroized on drop when `T: Zeroize` (`secret` feature)
+///
+/// Generated by `#[var(secret)]`, the value is reached with [`expose`](Secret::expose).
+/// Statics are never dropped, so the value of a `#[var(secret)]` stays in memory until the process exits.
+pub struct Secret<T> {
+    value: T,
+    wipe: Option<fn(&mut T)>,
+}
+
+#[cfg(feature = "secret")]
+impl<T: Zeroize> Secret<T> {
+    pub fn new(value: T) -> Secret<T> {
+        Self::with_wipe(value, Some(Zeroize::zeroize))
+    }
+}
+
+impl<T> Secret<T> {
+    /// Secret of type without `Zeroize`, the value isn't cleared on drop
+    pub fn without_zeroize(value: T) -> Secret<T> {
+        Self::with_wipe(value, None)
+    }
+
+    #[doc(hidden)]
+    pub fn with_wipe(value: T, wipe: Option<fn(&mut T)>) -> Secret<T> {
+        Secret { value, wipe }
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.value
+    }
+}
+
+#[cfg(feature = "std")]
+impl Secret<String> {
+    /// Reads secret file, e.g. Docker `/run/secrets/name`, without the trailing newline
+    ///
+    /// Zeroized on drop with `secret` feature.
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> std::io::Result<Secret<String>> {
+        let mut value = std::fs::read_to_string(path)?;
+        // Cleared bytes stay in capacity, which zeroize also wipes
+        let len = value.trim_end_matches(['\n', '\r']).len();
+        value.truncate(len);
+        #[cfg(feature = "secret")]
+        return Ok(Secret::new(value));
+        #[cfg(not(feature = "secret"))]
+        return Ok(Secret::without_zeroize(value));
+    }
+}
+
+impl<T: Clone> Clone for Secret<T> {
+    fn clone(&self) -> Self {
+        Self::with_wipe(self.value.clone(), self.wipe)
+    }
+}
+
+impl<T> Drop for Secret<T> {
+    fn drop(&mut self) {
+        if let Some(wipe) = self.wipe {
+            wipe(&mut self.value)
+        }
+    }
+}
+
+impl<T> Display for Secret<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+impl<T> Debug for Secret<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+// Picks `Zeroize` wipe when available
+#[doc(hidden)]
+#[cfg(feature = "secret")]
+pub trait WipeZeroize<T> {
+    fn wipe(&self) -> Option<fn(&mut T)>;
+}
+
+#[cfg(feature = "secret")]
+impl<T: Zeroize> WipeZeroize<T> for Probe<PhantomData<T>> {
+    fn wipe(&self) -> Option<fn(&mut T)> {
+        Some(Zeroize::zeroize)
+    }
+}
+
+#[doc(hidden)]
+#[cfg(feature = "secret")]
+pub trait WipeUnsupported<T> {
+    fn wipe(&self) -> Option<fn(&mut T)>;
+}
+
+#[cfg(feature = "secret")]
+impl<T> WipeUnsupported<T> for &Probe<PhantomData<T>> {
+    fn wipe(&self) -> Option<fn(&mut T)> {
+        None
+    }
+}
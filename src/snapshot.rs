@@ -0,0 +1,146 @@
+use crate::private::Probe;
+use crate::registry::{register, registered, Registered};
+use crate::{Lazy, LazyMut, LazyTtl, LazyValue, LazyWatch};
+use serde::Serialize;
+use serde_json::Value;
+use std::any::Any;
+use std::sync::Arc;
+
+/// Value of `#[var(secret)]` in [`snapshot_vars`]
+pub const REDACTED: &str = "[redacted]";
+
+/// Current value of a var kind, `null` until initialized, never starts init
+#[doc(hidden)]
+pub trait VarSnapshot {
+    fn snapshot_value(&self) -> Option<Value>;
+}
+
+fn _to_value<T: Serialize>(value: Option<&T>) -> Option<Value> {
+    match value {
+        Some(value) => serde_json::to_value(value).ok(),
+        None => Some(Value::Null),
+    }
+}
+
+impl<T: Serialize + Send + Sync + 'static> VarSnapshot for Lazy<T> {
+    fn snapshot_value(&self) -> Option<Value> {
+        _to_value(self.try_get().as_deref())
+    }
+}
+
+impl<T: Serialize> VarSnapshot for LazyValue<T> {
+    fn snapshot_value(&self) -> Option<Value> {
+        _to_value(self.try_get())
+    }
+}
+
+impl<T: Serialize + Send + Sync + 'static> VarSnapshot for LazyTtl<T> {
+    fn snapshot_value(&self) -> Option<Value> {
+        _to_value(self.try_get().as_deref())
+    }
+}
+
+impl<T: Serialize + Send + Sync + 'static> VarSnapshot for LazyWatch<T> {
+    fn snapshot_value(&self) -> Option<Value> {
+        _to_value(self.try_get().as_deref())
+    }
+}
+
+impl<T: Serialize + Send + Sync + 'static> VarSnapshot for LazyMut<T> {
+    fn snapshot_value(&self) -> Option<Value> {
+        let value = self._with_current(|lock| _to_value(Some(&*lock.read().unwrap_or_else(|e| e.into_inner()))));
+        value.unwrap_or(Some(Value::Null))
+    }
+}
+
+// Skipped while write locked, the snapshot never waits
+#[cfg(feature = "tokio")]
+impl<T: Serialize + Send + Sync + 'static> VarSnapshot for crate::LazyMutAsync<T> {
+    fn snapshot_value(&self) -> Option<Value> {
+        let value = self._with_current(|lock| _to_value(Some(&*lock.try_read().ok()?)));
+        value.unwrap_or(Some(Value::Null))
+    }
+}
+
+/// Var entry of the registry, adds name and value for [`snapshot_vars`]
+#[doc(hidden)]
+pub struct RegisteredVar<L: 'static> {
+    pub lazy: &'static L,
+    pub name: &'static str,
+    pub secret: bool,
+    /// `None` when the value is not `Serialize`
+    pub snapshot: fn(&'static L) -> Option<Value>,
+}
+
+impl<L: Registered> Registered for RegisteredVar<L> {
+    fn id(&self) -> String {
+        self.lazy.id()
+    }
+
+    fn type_name(&self) -> &'static str {
+        self.lazy.type_name()
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        self.lazy.tags()
+    }
+
+    fn resolve(&'static self) -> Arc<dyn Any + Send + Sync> {
+        self.lazy.resolve()
+    }
+
+    fn snapshot(&self) -> Option<(&'static str, Value)> {
+        if self.secret {
+            return Some((self.name, Value::from(REDACTED)));
+        }
+        (self.snapshot)(self.lazy).map(|value| (self.name, value))
+    }
+}
+
+/// Adds var to the registry and [`snapshot_vars`], `secret` values are [`REDACTED`]
+///
+/// `#[var]` is added automatically with `auto-register` feature.
+pub fn register_var<L: Registered + VarSnapshot>(name: &'static str, lazy: &'static L, secret: bool) {
+    let var = RegisteredVar {
+        lazy,
+        name,
+        secret,
+        snapshot: L::snapshot_value,
+    };
+    register(Box::leak(Box::new(var)));
+}
+
+/// Current values of registered vars as JSON object keyed by var name
+///
+/// Uninitialized vars are `null`, they are never initialized by the snapshot.
+/// Vars without `Serialize` are skipped, `#[var(secret)]` values are [`REDACTED`].
+///
+/// Without `auto-register` feature only vars added with [`register_var`] are listed.
+/// `thread_local` vars are never listed, their value differs per thread.
+pub fn snapshot_vars() -> Value {
+    let vars = registered().into_iter().filter_map(|entry| entry.snapshot());
+    Value::Object(vars.map(|(name, value)| (name.to_string(), value)).collect())
+}
+
+// Picks `Serialize` snapshot when available
+#[doc(hidden)]
+pub trait SnapshotSerialize {
+    fn snapshot(&self) -> Option<Value>;
+}
+
+impl<L: VarSnapshot> SnapshotSerialize for Probe<&'static L> {
+    fn snapshot(&self) -> Option<Value> {
+        self.0.snapshot_value()
+    }
+}
+
+#[doc(hidden)]
+pub trait SnapshotUnsupported {
+    fn snapshot(&self) -> Option<Value>;
+}
+
+impl<L> SnapshotUnsupported for &Probe<&'static L> {
+    fn snapshot(&self) -> Option<Value> {
+        None
+    }
+}
@@ -0,0 +1,143 @@
+#[cfg(feature = "testing")]
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+
+/// Test with isolated service graph, `#[wildbird::testing::test]`
+///
+/// Not exported as `wildbird::test`, `use wildbird::*` would replace the built-in `#[test]`.
+pub use crate::derive::wildbird_test as test;
+
+// Shared out of the state lock, closures may call the mock again
+#[cfg(feature = "testing")]
+type Shared<F> = Arc<Mutex<Box<F>>>;
+
+#[cfg(feature = "testing")]
+struct ExpectationState<F: ?Sized, M: ?Sized> {
+    returning: Option<Shared<F>>,
+    matcher: Option<Shared<M>>,
+    times: Option<usize>,
+    calls: usize,
+}
+
+/// Mocked method expectation, used by `#[service_mock]`
+///
+/// `F` is the `returning` closure type, `M` the argument matcher type.
+#[cfg(feature = "testing")]
+pub struct Expectation<F: ?Sized, M: ?Sized> {
+    name: &'static str,
+    state: Mutex<ExpectationState<F, M>>,
+}
+
+#[cfg(feature = "testing")]
+impl<F: ?Sized, M: ?Sized> Expectation<F, M> {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            state: Mutex::new(ExpectationState {
+                returning: None,
+                matcher: None,
+                times: None,
+                calls: 0,
+            }),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, ExpectationState<F, M>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn set_returning(&self, returning: Box<F>) {
+        self.state().returning = Some(Arc::new(Mutex::new(returning)));
+    }
+
+    pub fn set_matcher(&self, matcher: Box<M>) {
+        self.state().matcher = Some(Arc::new(Mutex::new(matcher)));
+    }
+
+    pub fn set_times(&self, times: usize) {
+        self.state().times = Some(times);
+    }
+
+    /// Number of calls so far
+    pub fn calls(&self) -> usize {
+        self.state().calls
+    }
+
+    /// Panics when expected number of calls doesn't match
+    pub fn checkpoint(&self) {
+        let state = self.state();
+        if let Some(times) = state.times {
+            if times != state.calls {
+                panic!(
+                    "{}: expected {times} call(s), received {}",
+                    self.name, state.calls
+                )
+            }
+        }
+    }
+
+    fn lock<'a, C: ?Sized>(&self, closure: &'a Shared<C>) -> MutexGuard<'a, Box<C>> {
+        match closure.try_lock() {
+            Ok(closure) => closure,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => panic!("{}: called again from its own closure", self.name),
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn check(&self, matches: impl FnOnce(&M) -> bool) {
+        let matcher = {
+            let mut state = self.state();
+            state.calls += 1;
+            if let Some(times) = state.times {
+                if state.calls > times {
+                    panic!("{}: expected {times} call(s), received more", self.name)
+                }
+            }
+            state.matcher.clone()
+        };
+        if let Some(matcher) = matcher {
+            if !matches(&self.lock(&matcher)) {
+                panic!("{}: called with unexpected arguments", self.name)
+            }
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn returning<R>(&self, call: impl FnOnce(&mut F) -> R) -> R {
+        let returning = self.state().returning.clone();
+        match returning {
+            Some(returning) => call(&mut self.lock(&returning)),
+            None => panic!("{}: no returning() value set", self.name),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod test_expectation {
+    use super::Expectation;
+
+    type GetExpectation = Expectation<dyn FnMut(u32) -> u32 + Send, dyn Fn(&u32) -> bool + Send>;
+
+    #[test]
+    fn should_count_calls() {
+        let expectation = GetExpectation::new("Repo::get");
+        expectation.set_returning(Box::new(|v| v + 1));
+        expectation.set_matcher(Box::new(|v| *v < 10));
+        expectation.set_times(2);
+
+        for v in 0..2 {
+            expectation.check(|m| m(&v));
+            assert_eq!(v + 1, expectation.returning(|f| f(v)));
+        }
+        assert_eq!(2, expectation.calls());
+        expectation.checkpoint();
+    }
+
+    #[test]
+    #[should_panic(expected = "Repo::get: called with unexpected arguments")]
+    fn should_match_arguments() {
+        let expectation = GetExpectation::new("Repo::get");
+        expectation.set_matcher(Box::new(|v| *v < 10));
+        expectation.check(|m| m(&10));
+    }
+}
@@ -359,12 +359,12 @@ mod async_map {
 }
 pub use async_map::*;
 
-pub static CPU_POOL: crate::Lazy<rayon::ThreadPool> = crate::Lazy::new(|| {
+pub static CPU_POOL: crate::Lazy<rayon::ThreadPool> = crate::Lazy::shared(|| {
     let cpus = number_of_cpus();
     build_thread_pool(cpus / 2, "CPU").expect("Unable to create Cpu thread pool")
 });
 
-pub static IO_POOL: crate::Lazy<rayon::ThreadPool> = crate::Lazy::new(|| {
+pub static IO_POOL: crate::Lazy<rayon::ThreadPool> = crate::Lazy::shared(|| {
     let cpus = number_of_cpus();
     build_thread_pool(cpus * 4, "I/O").expect("Unable to create I/O thread pool")
 });
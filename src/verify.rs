@@ -0,0 +1,183 @@
+use crate::inject::{InjectScope, InjectStack, OverrideGuard};
+use crate::panics::{self, CaughtPanic};
+use crate::registry::{registered, Registered};
+use crate::Lazy;
+use std::fmt::{self, Display, Formatter};
+
+/// Constructor panic
+#[derive(Debug, Clone)]
+pub struct InitPanic {
+    /// `None` when the panicking `Lazy` is not registered
+    pub type_name: Option<&'static str>,
+    pub id: String,
+    pub message: String,
+    pub location: Option<String>,
+}
+
+/// Registered service that failed because of its dependency
+#[derive(Debug, Clone)]
+pub struct Unresolved {
+    pub type_name: &'static str,
+    pub id: String,
+    /// Id of the failing dependency
+    pub cause: String,
+}
+
+/// Result of [`verify`]
+#[derive(Debug, Clone, Default)]
+pub struct VerifyReport {
+    pub resolved: Vec<&'static str>,
+    /// Ids in dependency order, without repeating the first one
+    pub cycles: Vec<Vec<String>>,
+    pub panics: Vec<InitPanic>,
+    pub unresolved: Vec<Unresolved>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.cycles.is_empty() && self.panics.is_empty() && self.unresolved.is_empty()
+    }
+
+    /// Panics with the report, when any service failed
+    #[track_caller]
+    pub fn assert_ok(&self) {
+        if !self.is_ok() {
+            panic!("{self}")
+        }
+    }
+
+    fn add_cycle(&mut self, mut ids: Vec<String>) -> Option<String> {
+        let last = ids.pop()?;
+        let start = ids.iter().position(|id| *id == last)?;
+        let mut cycle = ids.split_off(start);
+
+        let min = (0..cycle.len()).min_by_key(|i| &cycle[*i]).unwrap_or(0);
+        cycle.rotate_left(min);
+        let first = cycle.first().cloned();
+        if !self.cycles.contains(&cycle) {
+            self.cycles.push(cycle);
+        }
+        first
+    }
+
+    fn add_panic(&mut self, id: String, caught: CaughtPanic) {
+        if self.panics.iter().any(|p| p.id == id) {
+            return;
+        }
+        self.panics.push(InitPanic {
+            type_name: None,
+            id,
+            message: caught.message,
+            location: caught.location,
+        });
+    }
+
+    fn add_failure(&mut self, entry: &dyn Registered, caught: CaughtPanic) {
+        let id = entry.id();
+        let cause = if let Some(cycle) = caught.cycle.clone() {
+            match self.add_cycle(cycle) {
+                Some(cause) => cause,
+                None => return self.add_panic(id, caught),
+            }
+        } else {
+            let origin = caught.stack.last().cloned().unwrap_or_else(|| id.clone());
+            self.add_panic(origin.clone(), caught);
+            origin
+        };
+
+        let in_cycle = self.cycles.iter().any(|cycle| cycle.contains(&id));
+        let failed = self.panics.iter().any(|p| p.id == id);
+        if !in_cycle && !failed {
+            self.unresolved.push(Unresolved {
+                type_name: entry.type_name(),
+                id,
+                cause,
+            });
+        }
+    }
+}
+
+impl Display for VerifyReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return writeln!(f, "Service graph OK: {} resolved", self.resolved.len());
+        }
+        writeln!(f, "Service graph verification failed:")?;
+        for cycle in self.cycles.iter() {
+            writeln!(f, "  cycle: {} -> {}", cycle.join(" -> "), cycle[0])?;
+        }
+        for panic in self.panics.iter() {
+            let type_name = panic.type_name.unwrap_or("Lazy");
+            let location = panic.location.as_deref().unwrap_or("unknown");
+            writeln!(f, "  panic: {type_name} ({}) at {location}: {}", panic.id, panic.message)?;
+        }
+        for unresolved in self.unresolved.iter() {
+            writeln!(
+                f,
+                "  unresolved: {} ({}) caused by {}",
+                unresolved.type_name, unresolved.id, unresolved.cause
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Service graph verification
+///
+/// Resolves every [`registered`](crate::registered) service, dependencies first.
+/// With stubs the graph is resolved in an isolated [`InjectScope`], leaving global instances untouched.
+#[derive(Default)]
+pub struct Verify {
+    stubs: Vec<Box<dyn FnOnce() -> OverrideGuard>>,
+    isolated: bool,
+}
+
+impl Verify {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the service during verification, e.g. to skip side effects
+    pub fn stub<T: Send + Sync + 'static>(mut self, lazy: &'static Lazy<T>, value: impl Into<T>) -> Self {
+        let value = value.into();
+        self.stubs.push(Box::new(move || lazy.set_override(value)));
+        self.isolated = true;
+        self
+    }
+
+    /// Resolve in an isolated scope, without stubs
+    pub fn isolated(mut self) -> Self {
+        self.isolated = true;
+        self
+    }
+
+    pub fn run(self) -> VerifyReport {
+        let _hook = panics::install_hook();
+        let scope = self.isolated.then(InjectScope::isolate);
+        let guards: Vec<OverrideGuard> = self.stubs.into_iter().map(|stub| stub()).collect();
+
+        let entries = registered();
+        let mut report = VerifyReport::default();
+        for entry in entries.iter() {
+            let stack = InjectStack::snapshot();
+            let result = panics::catch(|| entry.resolve());
+            InjectStack::restore(stack.into());
+            match result {
+                Ok(_) => report.resolved.push(entry.type_name()),
+                Err(caught) => report.add_failure(*entry, caught),
+            }
+        }
+        for panic in report.panics.iter_mut() {
+            panic.type_name = entries.iter().find(|e| e.id() == panic.id).map(|e| e.type_name());
+        }
+
+        drop(guards);
+        drop(scope);
+        report
+    }
+}
+
+/// Resolves every registered service, see [`Verify`]
+pub fn verify() -> VerifyReport {
+    Verify::new().run()
+}
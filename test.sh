@@ -12,5 +12,25 @@ test_feature "rayon"
 test_feature "tokio"
 test_feature "timed-log"
 test_feature "timed-tracing"
+test_feature "lazy-stats"
+test_feature "timed-access"
+test_feature "timed-log,lazy-stats"
+test_feature "testing"
+test_feature "auto-register"
+test_feature "serde"
+test_feature "serde,auto-register"
+test_feature "secret"
+test_feature "serde,auto-register,secret"
+test_feature "persist"
 
+cargo build --no-default-features --features spin
+cargo build --no-default-features --features spin,secret
+cargo test -p wildbird_no_std_test
+
+# no_std build without the std linked by the test harness
+if rustup target list --installed | grep -q "thumbv7em-none-eabi"; then
+    cargo build -p wildbird_no_std_test --target thumbv7em-none-eabi
+else
+    echo "thumbv7em-none-eabi not installed, skipping (rustup target add thumbv7em-none-eabi)"
+fi
 cargo test
\ No newline at end of file
@@ -0,0 +1,86 @@
+#![cfg(feature = "auto-register")]
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test auto_register_test --features auto-register
+mod auto_register {
+    use wildbird::prelude::*;
+
+    #[service(construct = "init", tags = ["job"])]
+    struct CleanupJob {
+        name: String,
+    }
+
+    impl CleanupJob {
+        fn init() -> CleanupJob {
+            CleanupJob {
+                name: "cleanup".to_string(),
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct JobsConfig;
+
+    #[configuration]
+    impl JobsConfig {
+        fn job_limit(&self) -> usize {
+            4
+        }
+    }
+
+    #[var]
+    fn jobs_queue() -> String {
+        "jobs".to_string()
+    }
+
+    #[var(no_arc)]
+    fn worker_count() -> u16 {
+        2
+    }
+
+    #[var(ttl = "1m")]
+    fn rate_limit() -> u32 {
+        100
+    }
+
+    #[var(mutable)]
+    fn processed() -> i64 {
+        0
+    }
+
+    #[var(thread_local)]
+    fn worker_mark() -> char {
+        'w'
+    }
+
+    #[test]
+    fn should_register_every_var_kind() {
+        let registered = wildbird::registered();
+        let find = |name: &str| *registered.iter().find(|r| r.type_name() == name).unwrap();
+
+        let workers = find("u16").resolve();
+        assert_eq!(Some(&2), workers.downcast_ref::<&'static u16>().copied());
+        assert_eq!(Some(100), find("u32").resolve().downcast_ref::<u32>().copied());
+        let processed = find("i64").resolve();
+        assert_eq!(0, *processed.downcast_ref::<&'static LazyMut<i64>>().unwrap().read());
+        assert!(find("char").resolve().is::<&'static LazyLocal<char>>());
+    }
+
+    #[test]
+    fn should_register_at_link_time() {
+        let registered = wildbird::registered();
+        let names: Vec<&str> = registered.iter().map(|r| r.type_name()).collect();
+        assert!(names.iter().any(|n| n.ends_with("CleanupJob")));
+        assert!(names.contains(&"usize"));
+        assert!(names.contains(&"alloc::string::String"));
+
+        let jobs = wildbird::find_by_tag("job");
+        assert_eq!(1, jobs.len());
+        assert_eq!("cleanup", jobs[0].downcast::<CleanupJob>().unwrap().name);
+
+        wildbird::register(&CleanupJob);
+        assert_eq!(registered.len(), wildbird::registered().len());
+        wildbird::verify().assert_ok();
+    }
+}
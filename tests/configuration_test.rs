@@ -0,0 +1,98 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test configuration_test
+mod configuration {
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use wildbird::prelude::*;
+
+    #[service(construct = "init")]
+    struct Settings {
+        url: String,
+    }
+
+    impl Settings {
+        fn init() -> Self {
+            Self {
+                url: "postgres://localhost".to_string(),
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct AppConfig {
+        timeout_ms: u64,
+    }
+
+    #[configuration]
+    impl AppConfig {
+        fn connection(&self, settings: Arc<Settings>) -> String {
+            format!("{} ({}ms)", settings.url, self.timeout_ms)
+        }
+
+        async fn cache(&self) -> HashMap<String, String> {
+            std::thread::sleep(Duration::from_millis(10));
+            HashMap::from([("key".to_string(), "value".to_string())])
+        }
+
+        fn timeout() -> Duration {
+            Duration::from_millis(100)
+        }
+    }
+
+    #[derive(Default)]
+    struct RetryConfig {}
+
+    // Same method name as AppConfig::timeout
+    #[configuration]
+    impl RetryConfig {
+        fn timeout() -> u64 {
+            3
+        }
+    }
+
+    #[derive(Default)]
+    struct HTTPConfig {}
+
+    // Acronym runs stay one word: HTTP_CONFIG_USER_AGENT
+    #[configuration]
+    impl HTTPConfig {
+        fn user_agent() -> &'static str {
+            "wildbird"
+        }
+    }
+
+    #[inject]
+    fn configured_timeout(#[inject] timeout: Arc<Duration>, #[inject] retries: Arc<u64>) -> (Duration, u64) {
+        (*timeout, *retries)
+    }
+
+    #[service]
+    struct Repository {
+        connection: Arc<String>,
+        cache: Lazy<HashMap<String, String>>,
+    }
+
+    #[service(construct)]
+    fn repository_init(connection: Arc<String>, cache: Lazy<HashMap<String, String>>) -> Repository {
+        Repository { connection, cache }
+    }
+
+    #[test]
+    fn should_construct_from_configuration() {
+        assert_eq!("postgres://localhost (0ms)", APP_CONFIG_CONNECTION.as_str());
+        assert_eq!(Some(&"value".to_string()), APP_CONFIG_CACHE.get("key"));
+        assert_eq!(Duration::from_millis(100), *APP_CONFIG_TIMEOUT);
+        assert_eq!(3, *RETRY_CONFIG_TIMEOUT);
+        assert_eq!("wildbird", *HTTP_CONFIG_USER_AGENT);
+    }
+
+    #[test]
+    fn should_inject_configuration_services() {
+        assert_eq!("postgres://localhost (0ms)", Repository.connection.as_str());
+        assert_eq!(1, Repository.cache.len());
+
+        assert_eq!((Duration::from_millis(100), 3), configured_timeout());
+    }
+}
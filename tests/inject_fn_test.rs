@@ -0,0 +1,70 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test inject_fn_test
+mod inject_fn {
+    use wildbird::prelude::*;
+
+    #[service(construct = "init")]
+    struct Db {
+        name: String,
+    }
+
+    impl Db {
+        fn init() -> Db {
+            Db {
+                name: "jobs-db".to_string(),
+            }
+        }
+    }
+
+    #[service(construct = "init")]
+    struct Mailer {
+        from: String,
+    }
+
+    impl Mailer {
+        fn init() -> Mailer {
+            Mailer {
+                from: "noreply".to_string(),
+            }
+        }
+    }
+
+    struct Job {
+        id: u32,
+    }
+
+    #[inject]
+    fn handle(job: Job, #[inject] db: Arc<Db>, #[inject] mailer: &Mailer) -> String {
+        format!("{} {} {}", job.id, db.name, mailer.from)
+    }
+
+    #[inject]
+    async fn handle_async(#[inject] db: Lazy<Db>, job: Job) -> String {
+        format!("{} {}", job.id, db.name)
+    }
+
+    struct Worker {
+        name: String,
+    }
+
+    impl Worker {
+        #[inject]
+        fn run(&self, #[inject] mut db: Arc<Db>) -> String {
+            db = db.clone();
+            format!("{} {}", self.name, db.name)
+        }
+    }
+
+    #[test]
+    fn should_inject_fn_parameters() {
+        assert_eq!("1 jobs-db noreply", handle(Job { id: 1 }));
+        assert_eq!("2 jobs-db", handle_async(Job { id: 2 }).block());
+
+        let worker = Worker {
+            name: "worker".to_string(),
+        };
+        assert_eq!("worker jobs-db", worker.run());
+    }
+}
@@ -0,0 +1,234 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test intercept_test
+mod intercept {
+    use std::sync::Mutex;
+    use wildbird::intercept::Call;
+    use wildbird::prelude::*;
+
+    static EVENTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    fn take_events() -> Vec<String> {
+        std::mem::take(&mut *EVENTS.lock().unwrap())
+    }
+
+    #[derive(Default)]
+    struct Recorder;
+
+    impl Interceptor for Recorder {
+        fn before(&self, call: &Call) {
+            EVENTS.lock().unwrap().push(format!("before {call}"));
+        }
+
+        fn after(&self, call: &Call) {
+            EVENTS.lock().unwrap().push(format!("after {call}"));
+        }
+
+        fn on_error(&self, call: &Call, error: &dyn std::fmt::Debug) {
+            EVENTS.lock().unwrap().push(format!("error {call} {error:?}"));
+        }
+    }
+
+    #[derive(Default)]
+    struct Outer;
+
+    impl Interceptor for Outer {
+        fn before(&self, call: &Call) {
+            EVENTS.lock().unwrap().push(format!("outer before {}", call.method()));
+        }
+
+        fn after(&self, call: &Call) {
+            EVENTS.lock().unwrap().push(format!("outer after {}", call.method()));
+        }
+    }
+
+    #[service(construct = "init")]
+    struct UserService {
+        name: String,
+    }
+
+    #[intercept(with = Outer, Recorder)]
+    impl UserService {
+        fn init() -> UserService {
+            UserService {
+                name: "users".to_string(),
+            }
+        }
+
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn find(&self, id: u32) -> Result<String, String> {
+            if id == 0 {
+                return Err("not found".to_string());
+            }
+            let id: u32 = id.to_string().parse().map_err(|_| "parse".to_string())?;
+            Ok(format!("user {id}"))
+        }
+
+        async fn find_async(&self, id: u32) -> Result<String, String> {
+            let user = self.find(id)?;
+            Ok(format!("{user} async"))
+        }
+    }
+
+    #[test]
+    fn should_intercept_methods() {
+        let _ = UserService.name();
+        take_events();
+
+        assert_eq!("users", UserService.name());
+        assert_eq!(
+            vec![
+                "outer before name",
+                "before UserService::name",
+                "after UserService::name",
+                "outer after name",
+            ],
+            take_events()
+        );
+
+        assert_eq!(Err("not found".to_string()), UserService.find(0));
+        assert_eq!(
+            vec![
+                "outer before find",
+                "before UserService::find",
+                "error UserService::find \"not found\"",
+                "after UserService::find",
+                "outer after find",
+            ],
+            take_events()
+        );
+
+        assert_eq!(Ok("user 2 async".to_string()), UserService.find_async(2).block());
+        assert_eq!(8, take_events().len());
+    }
+}
+
+mod intercept_errors {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use wildbird::intercept::Call;
+    use wildbird::prelude::*;
+
+    static ERRORS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    static BUILT: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Default)]
+    struct ErrorLog;
+
+    impl Interceptor for ErrorLog {
+        fn on_error(&self, call: &Call, error: &dyn std::fmt::Debug) {
+            ERRORS.lock().unwrap().push(format!("{} {error:?}", call.method()));
+        }
+    }
+
+    type Parsed<T> = Result<T, std::num::ParseIntError>;
+
+    struct NoDebug;
+
+    #[service(construct = "init")]
+    struct Parser {}
+
+    #[intercept(with = ErrorLog)]
+    impl Parser {
+        fn init() -> Parser {
+            Parser {}
+        }
+
+        fn parse(&self, value: &str) -> Parsed<u32> {
+            value.parse()
+        }
+
+        fn read(&self) -> std::io::Result<String> {
+            Err(std::io::Error::other("closed"))
+        }
+
+        fn opaque(&self) -> Result<(), NoDebug> {
+            Err(NoDebug)
+        }
+
+        fn numbers(&self) -> Vec<impl Fn() -> u32> {
+            vec![|| 1, || 1]
+        }
+    }
+
+    #[test]
+    fn should_report_errors_of_result_aliases() {
+        assert_eq!(Ok(7), Parser.parse("7"));
+        assert!(Parser.parse("x").is_err());
+        assert!(Parser.read().is_err());
+        assert!(Parser.opaque().is_err());
+        assert_eq!(2, Parser.numbers().iter().map(|f| f()).sum::<u32>());
+
+        assert_eq!(
+            vec![
+                "parse ParseIntError { kind: InvalidDigit }",
+                "read Custom { kind: Other, error: \"closed\" }",
+                "opaque \"<error>\"",
+            ],
+            *ERRORS.lock().unwrap()
+        );
+    }
+
+    struct Counted;
+
+    impl Default for Counted {
+        fn default() -> Self {
+            BUILT.fetch_add(1, Ordering::SeqCst);
+            Counted
+        }
+    }
+
+    impl Interceptor for Counted {}
+
+    #[service(construct = "init")]
+    struct Ping {}
+
+    #[intercept(with = Counted)]
+    impl Ping {
+        fn init() -> Ping {
+            Ping {}
+        }
+
+        fn ping(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn should_build_interceptors_once_per_method() {
+        for _ in 0..3 {
+            assert!(Ping.ping());
+        }
+        assert_eq!(1, BUILT.load(Ordering::SeqCst));
+    }
+}
+
+#[cfg(any(feature = "timed-log", feature = "timed-tracing"))]
+mod intercept_timed {
+    use wildbird::metric::Timed;
+    use wildbird::prelude::*;
+
+    #[service(construct = "init")]
+    struct TimedService {}
+
+    #[intercept(with = Timed)]
+    impl TimedService {
+        fn init() -> TimedService {
+            TimedService {}
+        }
+
+        fn work(&self) -> Result<u32, String> {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            Err("failed".to_string())
+        }
+    }
+
+    #[test]
+    fn should_intercept_with_timed() {
+        assert!(TimedService.work().is_err());
+    }
+}
@@ -0,0 +1,80 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test lazy_inspect_test
+mod lazy_inspect {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use wildbird::prelude::*;
+
+    #[service(construct = "init")]
+    struct Metrics {
+        name: String,
+    }
+
+    impl Metrics {
+        fn init() -> Metrics {
+            std::thread::sleep(Duration::from_millis(50));
+            Metrics {
+                name: "metrics".to_string(),
+            }
+        }
+    }
+
+    static INIT_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[test]
+    fn should_inspect_without_init() {
+        Metrics.on_init(|metrics| {
+            assert_eq!("metrics", metrics.name);
+            INIT_CALLS.fetch_add(1, Ordering::SeqCst);
+        });
+        assert!(!Metrics.is_initialized());
+        assert!(Metrics.try_get().is_none());
+        assert!(Metrics.wait_initialized(Duration::from_millis(5)).is_none());
+        assert_eq!(0, INIT_CALLS.load(Ordering::SeqCst));
+
+        let builder = std::thread::spawn(|| Metrics.instance());
+        let waited = Metrics.wait_initialized(Duration::from_secs(5)).unwrap();
+        assert!(Arc::ptr_eq(&waited, &builder.join().unwrap()));
+
+        assert!(Metrics.is_initialized());
+        assert_eq!("metrics", Metrics.try_get().unwrap().name);
+        assert_eq!(1, INIT_CALLS.load(Ordering::SeqCst));
+
+        // Registered after init, runs immediately
+        Metrics.on_init(|_| {
+            INIT_CALLS.fetch_add(10, Ordering::SeqCst);
+        });
+        assert_eq!(11, INIT_CALLS.load(Ordering::SeqCst));
+    }
+
+    #[service(construct = "init")]
+    struct Tracer {}
+
+    impl Tracer {
+        fn init() -> Tracer {
+            std::thread::sleep(Duration::from_millis(20));
+            Tracer {}
+        }
+    }
+
+    static TRACER_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[test]
+    fn should_run_callback_once_when_registered_during_init() {
+        let builder = std::thread::spawn(|| Tracer.instance());
+        let registrars: Vec<_> = (0..8)
+            .map(|_| {
+                std::thread::spawn(|| {
+                    Tracer.on_init(|_| {
+                        TRACER_CALLS.fetch_add(1, Ordering::SeqCst);
+                    })
+                })
+            })
+            .collect();
+        builder.join().unwrap();
+        registrars.into_iter().for_each(|r| r.join().unwrap());
+        assert_eq!(8, TRACER_CALLS.load(Ordering::SeqCst));
+    }
+}
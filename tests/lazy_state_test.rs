@@ -0,0 +1,119 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test lazy_state_test
+mod lazy_state {
+    use std::panic::catch_unwind;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use wildbird::prelude::*;
+    use wildbird::{InitPolicy, LazyState};
+
+    static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+    #[var]
+    fn flaky() -> String {
+        if ATTEMPTS.fetch_add(1, Ordering::SeqCst) == 0 {
+            panic!("first attempt failed")
+        }
+        "connected".to_string()
+    }
+
+    #[service(construct = "init")]
+    struct Client {
+        connection: Arc<String>,
+    }
+
+    impl Client {
+        fn init() -> Client {
+            Client {
+                connection: FLAKY.instance(),
+            }
+        }
+    }
+
+    #[test]
+    fn should_retry_after_panic() {
+        assert_eq!(LazyState::Uninit, Client.state());
+        assert!(catch_unwind(|| Client.instance()).is_err());
+        assert_eq!(
+            LazyState::Failed("first attempt failed".to_string()),
+            FLAKY.state()
+        );
+
+        // Retry on the same thread is not reported as circular dependency
+        assert_eq!("connected", Client.connection.as_str());
+        assert_eq!(LazyState::Ready, FLAKY.state());
+        assert_eq!(LazyState::Ready, Client.state());
+    }
+
+    static FAIL_FAST_ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+    #[var(init_policy = "fail_fast")]
+    fn fail_fast() -> String {
+        if FAIL_FAST_ATTEMPTS.fetch_add(1, Ordering::SeqCst) == 0 {
+            panic!("config file missing")
+        }
+        "loaded".to_string()
+    }
+
+    #[test]
+    fn should_fail_fast_with_original_message() {
+        assert!(catch_unwind(|| FAIL_FAST.instance()).is_err());
+        let retry = catch_unwind(|| FAIL_FAST.instance()).unwrap_err();
+        let message = retry.downcast_ref::<String>().unwrap();
+        assert!(message.contains("config file missing"));
+        assert_eq!(1, FAIL_FAST_ATTEMPTS.load(Ordering::SeqCst));
+    }
+
+    static MANUAL: Lazy<u32> = Lazy::<u32>::new(|| panic!("manual")).with_policy(InitPolicy::FailFast);
+
+    #[test]
+    #[should_panic(expected = "Lazy initialization failed")]
+    fn should_fail_fast_manual_lazy() {
+        let _ = catch_unwind(|| *MANUAL);
+        let _ = *MANUAL;
+    }
+
+    static BLOCKED_ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+    static BLOCKED: Lazy<u32> = Lazy::<u32>::new(|| {
+        BLOCKED_ATTEMPTS.fetch_add(1, Ordering::SeqCst);
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        panic!("slow init failed")
+    })
+    .with_policy(InitPolicy::FailFast);
+
+    #[test]
+    fn should_fail_fast_blocked_waiters() {
+        let first = std::thread::spawn(|| catch_unwind(|| *BLOCKED).is_err());
+        while BLOCKED_ATTEMPTS.load(Ordering::SeqCst) == 0 {
+            std::thread::yield_now();
+        }
+        // Blocked until the first init fails
+        let waiter = std::thread::spawn(|| catch_unwind(|| *BLOCKED).unwrap_err());
+        assert!(first.join().unwrap());
+        let message = waiter.join().unwrap();
+        assert!(message.downcast_ref::<String>().unwrap().contains("slow init failed"));
+        assert_eq!(1, BLOCKED_ATTEMPTS.load(Ordering::SeqCst));
+    }
+
+    static SCOPED_ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+    #[var(init_policy = "fail_fast")]
+    fn scoped_fail_fast() -> u32 {
+        SCOPED_ATTEMPTS.fetch_add(1, Ordering::SeqCst);
+        panic!("scoped init failed")
+    }
+
+    #[test]
+    fn should_track_state_in_scope() {
+        let _scope = wildbird::InjectScope::isolate();
+        assert_eq!(LazyState::Uninit, SCOPED_FAIL_FAST.state());
+        assert!(catch_unwind(|| SCOPED_FAIL_FAST.instance()).is_err());
+        assert_eq!(
+            LazyState::Failed("scoped init failed".to_string()),
+            SCOPED_FAIL_FAST.state()
+        );
+        assert!(catch_unwind(|| SCOPED_FAIL_FAST.instance()).is_err());
+        assert_eq!(1, SCOPED_ATTEMPTS.load(Ordering::SeqCst));
+    }
+}
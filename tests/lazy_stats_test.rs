@@ -0,0 +1,55 @@
+#![cfg(feature = "lazy-stats")]
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test lazy_stats_test --features lazy-stats
+mod lazy_stats {
+    use wildbird::metric::lazy_stats;
+    use wildbird::prelude::*;
+
+    #[var]
+    fn slow_config() -> String {
+        "config".to_string()
+    }
+
+    #[test]
+    fn should_record_init_stats() {
+        let before = SLOW_CONFIG.stats();
+        assert!(before.init_duration.is_none());
+        assert!(before.first_access.is_none());
+
+        let handle = std::thread::Builder::new()
+            .name("config-loader".to_string())
+            .spawn(|| SLOW_CONFIG.len())
+            .unwrap();
+        assert_eq!(handle.join().unwrap(), 6);
+        assert_eq!(SLOW_CONFIG.as_str(), "config");
+
+        let stats = SLOW_CONFIG.stats();
+        assert!(stats.type_name.contains("String"));
+        assert!(stats.first_access.is_some());
+        assert!(stats.init_duration.is_some());
+        assert_eq!(stats.built_by.as_deref(), Some("config-loader"));
+        #[cfg(feature = "timed-access")]
+        assert_eq!(stats.accesses, Some(2));
+        #[cfg(not(feature = "timed-access"))]
+        assert_eq!(stats.accesses, None);
+
+        assert_eq!(1, lazy_stats().iter().filter(|s| s.id == stats.id).count());
+        assert!(stats.to_string().contains("on config-loader"));
+    }
+    #[var]
+    fn shared_limits() -> Vec<u32> {
+        vec![1, 2]
+    }
+
+    #[test]
+    fn should_share_stats_with_clones() {
+        let clones: Vec<_> = (0..3).map(|_| SHARED_LIMITS.clone_lazy()).collect();
+        assert_eq!(2, clones[2].len());
+
+        let id = SHARED_LIMITS.stats().id;
+        assert_eq!(1, lazy_stats().iter().filter(|s| s.id == id).count());
+        assert_eq!(clones[0].stats().init_duration, SHARED_LIMITS.stats().init_duration);
+    }
+}
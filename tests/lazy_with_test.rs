@@ -0,0 +1,71 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test lazy_with_test
+mod lazy_with {
+    use std::panic::catch_unwind;
+    use wildbird::prelude::*;
+    use wildbird::LazyState;
+
+    #[test]
+    fn should_init_with_captured_config() {
+        let config = Arc::new(String::from("postgres://localhost"));
+        let captured = config.clone();
+        let connection = LazyWith::new(move || format!("connected to {captured}"));
+
+        assert!(!connection.is_initialized());
+        assert_eq!("(Not initialized)", connection.to_string());
+        assert_eq!(2, Arc::strong_count(&config));
+
+        assert_eq!("connected to postgres://localhost", *connection);
+        assert_eq!("connected to postgres://localhost", connection.to_string());
+        assert!(Arc::ptr_eq(&connection.instance(), &connection.try_get().unwrap()));
+
+        // Closure with captured config was dropped
+        assert_eq!(1, Arc::strong_count(&config));
+    }
+
+    static PING: LazyWith<u32> = LazyWith::new(|| *PONG + 1);
+    static PONG: LazyWith<u32> = LazyWith::new(|| *PING + 1);
+
+    #[test]
+    fn should_detect_cycle() {
+        let cycle = catch_unwind(|| *PING).unwrap_err();
+        assert!(cycle.downcast_ref::<String>().unwrap().contains("Circular dependency"));
+    }
+
+    #[test]
+    #[should_panic(expected = "no config")]
+    fn should_report_panicked_initializer() {
+        let failing: LazyWith<u32, _> = LazyWith::new(|| panic!("no config"));
+        assert!(catch_unwind(|| *failing).is_err());
+        assert!(matches!(failing.state(), LazyState::Failed(message) if message == "no config"));
+        let _ = *failing;
+    }
+
+    static ENDPOINT: LazyWith<String> = LazyWith::new(|| String::from("https://prod"));
+
+    #[test]
+    fn should_override_in_scope() {
+        {
+            let _guard = ENDPOINT.set_override("https://test");
+            assert_eq!("https://test", *ENDPOINT);
+        }
+        assert_eq!("https://prod", *ENDPOINT);
+        assert_eq!(LazyState::Ready, ENDPOINT.state());
+    }
+    fn connection(url: &'static str) -> LazyWith<String, impl FnOnce() -> String> {
+        LazyWith::new(move || format!("connected to {url}"))
+    }
+
+    #[test]
+    fn should_keep_own_id_per_instance() {
+        let primary = connection("primary");
+        let replica = connection("replica");
+        let _scope = wildbird::InjectScope::isolate();
+        let _guard = primary.set_override("primary override");
+
+        assert_eq!("primary override", *primary);
+        assert_eq!("connected to replica", *replica);
+    }
+}
@@ -0,0 +1,67 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test s_blocking_wrappers
+mod blocking_wrappers {
+    use wildbird::prelude::*;
+
+    struct Storage {
+        bucket: String,
+    }
+
+    #[service(blocking_wrappers)]
+    impl Storage {
+        #[construct]
+        async fn connect() -> Self {
+            Storage {
+                bucket: "files".to_string(),
+            }
+        }
+
+        pub async fn read(&self, name: &str, (from, to): (usize, usize)) -> String {
+            format!("{}/{}", self.bucket, &name[from..to])
+        }
+
+        async fn exists(name: String) -> bool {
+            !name.is_empty()
+        }
+
+        fn bucket(&self) -> &str {
+            &self.bucket
+        }
+    }
+
+    #[service(construct = "init")]
+    struct Cache {}
+
+    impl Cache {
+        fn init() -> Cache {
+            Cache {}
+        }
+    }
+
+    #[service(blocking_wrappers)]
+    impl Cache {
+        async fn get<K: ToString>(&self, key: K) -> Option<String> {
+            Some(key.to_string())
+        }
+    }
+
+    #[test]
+    fn should_generate_blocking_wrappers() {
+        assert_eq!("files/report", Storage.read_blocking("report.pdf", (0, 6)));
+        assert!(Storage::exists_blocking("a".to_string()));
+        assert_eq!("files", Storage.bucket());
+        assert_eq!(Some("7".to_string()), Cache.get_blocking(7));
+    }
+
+    #[test]
+    fn should_call_from_rayon_like_threads() {
+        let handles: Vec<_> = (0..4)
+            .map(|i| std::thread::spawn(move || Cache.get_blocking(i)))
+            .collect();
+        for (i, handle) in handles.into_iter().enumerate() {
+            assert_eq!(Some(i.to_string()), handle.join().unwrap());
+        }
+    }
+}
@@ -0,0 +1,58 @@
+#![allow(dead_code, unused_variables, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test s_impl_construct
+mod service {
+    use std::sync::Arc;
+    use std::time::Duration;
+    use wildbird::derive::*;
+
+    pub struct Database {
+        url: String,
+    }
+
+    #[service(public)]
+    impl Database {
+        #[construct]
+        pub fn connect() -> Self {
+            Self {
+                url: "db://local".to_string(),
+            }
+        }
+
+        pub fn url(&self) -> &str {
+            &self.url
+        }
+    }
+
+    struct UserService {
+        db: Arc<Database>,
+    }
+
+    #[service]
+    impl UserService {
+        #[construct]
+        async fn new(db: Arc<Database>) -> Self {
+            std::thread::sleep(Duration::from_millis(10));
+            Self { db }
+        }
+
+        fn describe(&self) -> String {
+            format!("users@{}", self.db.url())
+        }
+    }
+
+    #[test]
+    fn should_construct_from_impl() {
+        assert_eq!("db://local", Database.url());
+        let db: Arc<Database> = Database.instance();
+        assert_eq!("db://local", db.url());
+    }
+
+    #[test]
+    fn should_inject_async_construct_from_impl() {
+        assert_eq!("users@db://local", UserService.describe());
+        let service: &UserService = wildbird::Inject();
+        assert_eq!("users@db://local", service.describe());
+    }
+}
@@ -0,0 +1,69 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test s_tags
+mod tags {
+    use wildbird::prelude::*;
+
+    #[service(construct = "init", tags = ["http-route", "admin"])]
+    struct AdminRoute {
+        path: String,
+    }
+
+    impl AdminRoute {
+        fn init() -> AdminRoute {
+            AdminRoute {
+                path: "/admin".to_string(),
+            }
+        }
+    }
+
+    struct UsersRoute {
+        path: String,
+    }
+
+    #[service(tags = ["http-route"])]
+    impl UsersRoute {
+        #[construct]
+        fn new() -> Self {
+            UsersRoute {
+                path: "/users".to_string(),
+            }
+        }
+    }
+
+    #[service(construct = "init")]
+    struct Untagged {}
+
+    impl Untagged {
+        fn init() -> Untagged {
+            Untagged {}
+        }
+    }
+
+    #[test]
+    fn should_find_services_by_tag() {
+        wildbird::register(&AdminRoute);
+        wildbird::register(&UsersRoute);
+        wildbird::register(&Untagged);
+        assert_eq!(&["http-route", "admin"], AdminRoute.tags());
+
+        let routes = wildbird::find_by_tag("http-route");
+        assert_eq!(2, routes.len());
+        assert!(routes.iter().any(|r| r.type_name.ends_with("AdminRoute")));
+        assert!(routes[0].id.contains("s_tags.rs"));
+
+        let mut paths: Vec<String> = routes
+            .iter()
+            .filter_map(|route| {
+                let admin = route.downcast::<AdminRoute>().map(|r| r.path.clone());
+                admin.or_else(|| route.downcast::<UsersRoute>().map(|r| r.path.clone()))
+            })
+            .collect();
+        paths.sort();
+        assert_eq!(vec!["/admin", "/users"], paths);
+
+        assert_eq!(1, wildbird::find_by_tag("admin").len());
+        assert!(wildbird::find_by_tag("cli").is_empty());
+    }
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "testing")]
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test service_mock_test --features testing
+mod service_mock {
+    use wildbird::prelude::*;
+
+    #[service_mock]
+    pub trait UserRepo: Send + Sync {
+        fn get_user(&self, id: u32) -> String;
+        fn rename(&self, id: u32, name: &str) -> bool;
+    }
+
+    struct PgRepo;
+
+    impl UserRepo for PgRepo {
+        fn get_user(&self, id: u32) -> String {
+            format!("pg user {id}")
+        }
+
+        fn rename(&self, _id: u32, _name: &str) -> bool {
+            false
+        }
+    }
+
+    #[derive(Default)]
+    struct Repositories;
+
+    #[configuration]
+    impl Repositories {
+        fn user_repo(&self) -> Box<dyn UserRepo> {
+            Box::new(PgRepo)
+        }
+    }
+
+    struct UserService {
+        repo: Arc<Box<dyn UserRepo>>,
+    }
+
+    #[service]
+    impl UserService {
+        #[construct]
+        fn new(repo: Arc<Box<dyn UserRepo>>) -> Self {
+            Self { repo }
+        }
+
+        fn describe(&self, id: u32) -> String {
+            format!("[{}]", self.repo.get_user(id))
+        }
+    }
+
+    #[test]
+    fn should_swap_dependency_with_mock() {
+        let mock = MockUserRepo::new();
+        mock.expect_get_user()
+            .with(|id| *id == 7)
+            .times(1)
+            .returning(|id| format!("mock user {id}"));
+
+        let _guard = REPOSITORIES_USER_REPO.set_override(mock.clone());
+        assert_eq!("[mock user 7]", UserService.describe(7));
+        assert_eq!(1, mock.expect_get_user().calls());
+        mock.checkpoint();
+    }
+
+    #[test]
+    fn should_use_real_dependency_without_override() {
+        assert_eq!("[pg user 1]", UserService.describe(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "UserRepo::rename: called with unexpected arguments")]
+    fn should_match_arguments() {
+        let mock = MockUserRepo::new();
+        mock.expect_rename()
+            .with(|_, name| name.starts_with("admin"))
+            .returning(|_, _| true);
+        mock.rename(1, "guest");
+    }
+
+    #[test]
+    #[should_panic(expected = "UserRepo::get_user: expected 2 call(s), received 1")]
+    fn should_verify_calls_on_checkpoint() {
+        let mock = MockUserRepo::new();
+        mock.expect_get_user().times(2).returning(|_| String::new());
+        mock.get_user(1);
+        mock.checkpoint();
+    }
+
+    #[test]
+    fn should_call_mock_from_returning_closure() {
+        let mock = MockUserRepo::new();
+        let repo = mock.clone();
+        mock.expect_get_user().returning(|id| format!("user {id}"));
+        mock.expect_rename().returning(move |id, _| repo.get_user(id) == "user 1");
+        assert!(mock.rename(1, "bird"));
+    }
+
+    #[test]
+    #[should_panic(expected = "UserRepo::get_user: called again from its own closure")]
+    fn should_report_recursive_returning_closure() {
+        let mock = MockUserRepo::new();
+        let repo = mock.clone();
+        mock.expect_get_user().returning(move |id| repo.get_user(id + 1));
+        mock.get_user(1);
+    }
+
+    pub trait Notifier {
+        async fn notify(&self, message: String) -> usize;
+    }
+
+    struct EmailNotifier;
+
+    #[service_mock]
+    impl Notifier for EmailNotifier {
+        async fn notify(&self, message: String) -> usize {
+            message.len()
+        }
+    }
+
+    #[test]
+    fn should_mock_async_trait_impl() {
+        let mock = MockNotifier::new();
+        mock.expect_notify().returning(|_| 42);
+        assert_eq!(42, mock.notify("hello".to_string()).block());
+        assert_eq!(5, EmailNotifier.notify("hello".to_string()).block());
+    }
+}
@@ -0,0 +1,93 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test var_callback_errors
+mod var_callback_errors {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+    use wildbird::prelude::*;
+    use wildbird::InitError;
+
+    fn connected() -> bool {
+        std::env::var("WILDBIRD_TEST_CONNECTED").is_ok()
+    }
+
+    #[var(timeout = "50ms")]
+    async fn hanging(callback: Callback<u32>) {
+        std::thread::sleep(Duration::from_millis(500));
+        callback.call(1);
+    }
+
+    #[var]
+    async fn broken(callback: Callback<u32>) {
+        if !connected() {
+            panic!("database down");
+        }
+        callback.call(1);
+    }
+
+    #[var]
+    async fn silent(callback: Callback<u32>) {
+        if connected() {
+            callback.call(1);
+        }
+    }
+
+    #[var(timeout = "1s")]
+    async fn ready(callback: Callback<u32>) {
+        callback.call(7);
+    }
+
+    #[test]
+    fn should_time_out() {
+        assert_eq!(Err(InitError::Timeout(Duration::from_millis(50))), HANGING.try_instance());
+    }
+
+    #[test]
+    fn should_propagate_panic() {
+        match BROKEN.try_instance() {
+            Err(InitError::Panicked { message, location }) => {
+                assert_eq!("database down", message);
+                assert!(location.unwrap().starts_with("tests/var_callback_errors.rs"));
+            }
+            other => panic!("expected panic, got {other:?}"),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "database down")]
+    fn should_panic_readers_with_original_message() {
+        let _ = *BROKEN;
+    }
+
+    #[test]
+    fn should_report_missing_call() {
+        assert_eq!(Err(InitError::NotCalled), SILENT.try_instance());
+        assert_eq!(7, *READY.try_instance().unwrap());
+    }
+
+    static PUBLISHERS: AtomicU32 = AtomicU32::new(0);
+
+    #[var(timeout = "50ms")]
+    async fn stalled(callback: Callback<u32>) {
+        PUBLISHERS.fetch_add(1, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(300));
+        callback.call(3);
+    }
+
+    #[test]
+    fn should_await_timed_out_publisher() {
+        assert_eq!(Err(InitError::Timeout(Duration::from_millis(50))), STALLED.try_instance());
+        assert_eq!(Err(InitError::Timeout(Duration::from_millis(50))), STALLED.try_instance());
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        let value = loop {
+            match STALLED.try_instance() {
+                Ok(value) => break value,
+                Err(_) if std::time::Instant::now() < deadline => continue,
+                Err(error) => panic!("publisher never finished: {error:?}"),
+            }
+        };
+        assert_eq!(3, *value);
+        assert_eq!(1, PUBLISHERS.load(Ordering::SeqCst));
+    }
+}
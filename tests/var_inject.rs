@@ -0,0 +1,86 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test var_inject
+mod var_inject {
+    use std::panic::catch_unwind;
+    use wildbird::prelude::*;
+
+    #[service(construct = "init")]
+    struct Settings {
+        host: String,
+    }
+
+    impl Settings {
+        fn init() -> Settings {
+            Settings {
+                host: "http://localhost".to_string(),
+            }
+        }
+    }
+
+    #[var]
+    fn base_url(settings: Arc<Settings>) -> String {
+        format!("{}/api", settings.host)
+    }
+
+    #[var]
+    async fn users_url(settings: Arc<Settings>) -> String {
+        format!("{}/users", settings.host)
+    }
+
+    #[var(timeout = "1s")]
+    async fn health(callback: Callback<String>, settings: Arc<Settings>) {
+        callback.call(format!("{}/health", settings.host));
+    }
+
+    #[var(timeout = "1s")]
+    async fn docs_url(settings: Arc<Settings>, callback: wildbird::Callback<String>) {
+        callback.call(format!("{}/docs", settings.host));
+    }
+
+    // Named like a callback, injected as `Arc<Settings>`
+    type SettingsCallback<T> = Arc<T>;
+
+    #[var]
+    fn admin_url(settings: SettingsCallback<Settings>) -> String {
+        format!("{}/admin", settings.host)
+    }
+
+    #[var(watch)]
+    async fn status(settings: Arc<Settings>, callback: Callback<String>) {
+        callback.call(format!("{} up", settings.host));
+    }
+
+    #[service(construct)]
+    fn report_init() -> Report {
+        Report { url: LOOP_URL.as_str().to_string() }
+    }
+
+    #[service]
+    struct Report {
+        url: String,
+    }
+
+    #[var]
+    fn loop_url(report: Arc<Report>) -> String {
+        report.url.clone()
+    }
+
+    #[test]
+    fn should_inject_var_parameters() {
+        assert_eq!("http://localhost/api", BASE_URL.as_str());
+        assert_eq!("http://localhost/users", USERS_URL.as_str());
+        assert_eq!("http://localhost/health", HEALTH.as_str());
+        assert_eq!("http://localhost/docs", DOCS_URL.as_str());
+        assert_eq!("http://localhost/admin", ADMIN_URL.as_str());
+        assert_eq!("http://localhost up", STATUS.get().as_str());
+    }
+
+    #[test]
+    fn should_detect_cycle_through_parameter() {
+        let cycle = catch_unwind(|| LOOP_URL.len()).unwrap_err();
+        let message = cycle.downcast_ref::<String>().unwrap();
+        assert!(message.contains("Circular dependency"), "{message}");
+    }
+}
@@ -23,4 +23,45 @@ mod lazy {
         println!("Number: {:?}", PATH);
         println!("Number: {}", PATH.unwrap());
     }
-}
\ No newline at end of file
+
+    #[test]
+    pub fn should_override_on_current_thread() {
+        {
+            let _guard = PATH.set_override(Some(1));
+            assert_eq!(Some(1), *PATH);
+            std::thread::spawn(|| assert_eq!(Some(32), *PATH)).join().unwrap();
+        }
+        assert_eq!(Some(32), *PATH);
+    }
+
+    static DROPPED: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    struct Connection;
+
+    impl Drop for Connection {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    #[var]
+    fn connection() -> Connection {
+        Connection
+    }
+
+    #[test]
+    pub fn should_drop_scoped_instance_with_scope() {
+        let dropped = || DROPPED.load(std::sync::atomic::Ordering::SeqCst);
+        {
+            let _scope = wildbird::InjectScope::isolate();
+            let _owned: Arc<Connection> = CONNECTION.instance();
+        }
+        assert_eq!(1, dropped());
+        {
+            let _scope = wildbird::InjectScope::isolate();
+            let _borrowed: &'static Connection = CONNECTION.to_ref();
+        }
+        // Borrowed as 'static, kept alive
+        assert_eq!(1, dropped());
+    }
+}
@@ -0,0 +1,125 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test var_mutable
+mod var_mutable {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use wildbird::prelude::*;
+
+    #[var(mutable)]
+    fn plugins() -> HashMap<String, u32> {
+        HashMap::from([("core".to_string(), 1)])
+    }
+
+    #[var(mutable)]
+    fn hits() -> u64 {
+        0
+    }
+
+    #[var(mutable = "sync")]
+    fn status() -> String {
+        "starting".to_string()
+    }
+
+    #[test]
+    fn should_read_and_write() {
+        assert_eq!(Some(&1), PLUGINS.read().get("core"));
+        PLUGINS.write().insert("auth".to_string(), 2);
+        assert_eq!(2, PLUGINS.read().len());
+        assert_eq!(1, PLUGINS.version());
+    }
+
+    #[test]
+    fn should_update_from_threads() {
+        let threads: Vec<_> = (0..4)
+            .map(|_| std::thread::spawn(|| (0..100).for_each(|_| HITS.update(|hits| *hits += 1))))
+            .collect();
+        threads.into_iter().for_each(|t| t.join().unwrap());
+        assert_eq!(400, *HITS.read());
+        assert_eq!(400, HITS.version());
+    }
+
+    #[test]
+    fn should_replace_and_notify() {
+        static CHANGES: AtomicUsize = AtomicUsize::new(0);
+        STATUS.on_change(|status| {
+            assert_eq!("ready", status);
+            CHANGES.fetch_add(1, Ordering::SeqCst);
+        });
+        assert_eq!("starting", STATUS.replace("ready".to_string()));
+        assert_eq!("ready", STATUS.to_string());
+        assert_eq!(1, CHANGES.load(Ordering::SeqCst));
+    }
+
+    #[var(mutable)]
+    fn quota() -> u32 {
+        10
+    }
+
+    #[test]
+    fn should_isolate_in_scope() {
+        {
+            let _scope = wildbird::InjectScope::isolate();
+            *QUOTA.write() = 1;
+            assert_eq!(1, *QUOTA.read());
+            assert_eq!(1, QUOTA.version());
+        }
+        assert_eq!(10, *QUOTA.read());
+        assert_eq!(0, QUOTA.version());
+    }
+
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counter(u32);
+
+    impl Drop for Counter {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[var(mutable)]
+    fn counter() -> Counter {
+        Counter(0)
+    }
+
+    #[test]
+    fn should_free_scoped_value() {
+        {
+            let _scope = wildbird::InjectScope::isolate();
+            COUNTER.write().0 += 1;
+            assert_eq!(1, COUNTER.read().0);
+        }
+        assert_eq!(1, DROPPED.load(Ordering::SeqCst));
+        assert!(!COUNTER.is_initialized());
+    }
+
+    #[wildbird::testing::test]
+    fn should_get_fresh_value_a() {
+        QUOTA.update(|quota| *quota += 1);
+        assert_eq!(11, *QUOTA.read());
+    }
+
+    #[wildbird::testing::test]
+    fn should_get_fresh_value_b() {
+        QUOTA.update(|quota| *quota += 2);
+        assert_eq!(12, *QUOTA.read());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[var(mutable = "async")]
+    fn sessions() -> Vec<String> {
+        vec![]
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn should_lock_async() {
+        SESSIONS.write().await.push("a".to_string());
+        SESSIONS.update(|sessions| sessions.push("b".to_string())).await;
+        assert_eq!(vec!["a", "b"], *SESSIONS.read().await);
+        assert_eq!(vec!["a", "b"], SESSIONS.replace(vec![]).await);
+        assert_eq!(3, SESSIONS.version());
+    }
+}
@@ -0,0 +1,78 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test var_no_arc
+mod var_no_arc {
+    use std::panic::catch_unwind;
+    use wildbird::prelude::*;
+
+    #[var(no_arc)]
+    fn app_name() -> String {
+        "wildbird".to_string()
+    }
+
+    #[var(no_arc, name = "LIMITS")]
+    async fn fetch_limits() -> Vec<u32> {
+        vec![10, 20]
+    }
+
+    static RETRIES: LazyValue<u32> = LazyValue::new(|| 3);
+
+    static LOOP_A: LazyValue<u32> = LazyValue::new(|| *LOOP_B);
+    static LOOP_B: LazyValue<u32> = LazyValue::new(|| *LOOP_A);
+
+    #[test]
+    fn should_store_value_inline() {
+        assert!(!APP_NAME.is_initialized());
+        assert_eq!("(Not initialized)", APP_NAME.to_string());
+
+        let name: &'static String = APP_NAME.to_ref();
+        assert_eq!("wildbird", name);
+        assert_eq!(Some(name), APP_NAME.try_get());
+        assert_eq!("wildbird", APP_NAME.to_string());
+
+        assert_eq!(vec![10, 20], *LIMITS);
+        assert_eq!(3, *RETRIES);
+    }
+
+    #[test]
+    fn should_detect_cycle() {
+        let cycle = catch_unwind(|| *LOOP_A).unwrap_err();
+        assert!(cycle.downcast_ref::<String>().unwrap().contains("Circular dependency"));
+    }
+
+    struct Region {
+        name: String,
+    }
+
+    #[var(no_arc)]
+    fn region() -> Region {
+        Region { name: "eu-west".to_string() }
+    }
+
+    #[service(construct)]
+    fn router_init(region: &'static Region) -> Router {
+        Router { region: region.name.clone() }
+    }
+
+    #[service]
+    struct Router {
+        region: String,
+    }
+
+    #[inject]
+    fn region_name(#[inject] region: &'static Region) -> String {
+        region.name.clone()
+    }
+
+    #[test]
+    fn should_inject_static_reference() {
+        let region: &'static Region = Inject();
+        assert!(std::ptr::eq(region, REGION.to_ref()));
+        assert_eq!("eu-west", Router.region);
+        assert_eq!("eu-west", region_name());
+
+        let limits: &'static Vec<u32> = Inject();
+        assert_eq!(vec![10, 20], *limits);
+    }
+}
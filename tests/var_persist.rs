@@ -0,0 +1,71 @@
+#![cfg(feature = "persist")]
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test var_persist --features persist
+mod var_persist {
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+    use wildbird::persist::{cache_path, cache_path_in, load_or_compute_in};
+    use wildbird::prelude::*;
+
+    // Own cache dir per test
+    fn test_cache_dir(test: &str) -> PathBuf {
+        let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("wildbird_persist").join(test);
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[var(persist = "dictionary/words", version = 3)]
+    fn words() -> BTreeMap<String, u32> {
+        BTreeMap::from([("bird".to_string(), 1), ("wild".to_string(), 2)])
+    }
+
+    fn computed() -> Vec<u32> {
+        vec![1, 2, 3]
+    }
+
+    fn recomputed() -> Vec<u32> {
+        vec![4, 5, 6]
+    }
+
+    #[test]
+    fn should_cache_var_on_disk() {
+        assert_eq!(Some(&2), WORDS.get("wild"));
+
+        // WILDBIRD_CACHE_DIR in .cargo/config.toml, not the user cache
+        let path = cache_path(env!("CARGO_PKG_NAME"), "dictionary/words").unwrap();
+        assert!(path.starts_with(std::env::var_os("WILDBIRD_CACHE_DIR").unwrap()));
+        assert!(path.ends_with("wildbird/dictionary/words.json"));
+        let cached = std::fs::read_to_string(path).unwrap();
+        assert!(cached.contains("\"version\":3"), "{cached}");
+    }
+
+    #[test]
+    fn should_load_matching_version() {
+        let dir = test_cache_dir("matching_version");
+
+        assert_eq!(vec![1, 2, 3], load_or_compute_in(&dir, "numbers", 1, computed));
+        // Next run loads the cached value
+        assert_eq!(vec![1, 2, 3], load_or_compute_in(&dir, "numbers", 1, recomputed));
+        assert_eq!(vec![4, 5, 6], load_or_compute_in(&dir, "numbers", 2, recomputed));
+        assert_eq!(vec![4, 5, 6], load_or_compute_in(&dir, "numbers", 2, computed));
+    }
+
+    #[test]
+    fn should_recompute_corrupt_file() {
+        let dir = test_cache_dir("corrupt_file");
+        let path = cache_path_in(&dir, "corrupt");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "{\"key\":\"corrupt\",\"version\":1,\"val").unwrap();
+
+        assert_eq!(vec![1, 2, 3], load_or_compute_in(&dir, "corrupt", 1, computed));
+        assert_eq!(vec![1, 2, 3], load_or_compute_in(&dir, "corrupt", 1, recomputed));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid persist key")]
+    fn should_keep_key_in_cache_dir() {
+        cache_path_in(&test_cache_dir("escape"), "../outside");
+    }
+}
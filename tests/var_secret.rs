@@ -0,0 +1,78 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test var_secret
+mod var_secret {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use wildbird::prelude::*;
+    use zeroize::Zeroize;
+
+    #[var(secret)]
+    fn api_key() -> String {
+        "sk-123".to_string()
+    }
+
+    // Not Zeroize, kept as is
+    #[var(secret)]
+    fn pin() -> std::num::NonZeroU32 {
+        std::num::NonZeroU32::new(1234).unwrap()
+    }
+
+    #[var(secret_file = concat!(env!("CARGO_TARGET_TMPDIR"), "/wildbird_var_secret/db_port"))]
+    fn db_port() -> u16 {
+        5432
+    }
+
+    #[var(secret_file = concat!(env!("CARGO_TARGET_TMPDIR"), "/wildbird_var_secret/missing"))]
+    fn fallback_token() -> String {
+        "from-env".to_string()
+    }
+
+    static WIPED: AtomicBool = AtomicBool::new(false);
+
+    struct Key(u8);
+
+    impl Zeroize for Key {
+        fn zeroize(&mut self) {
+            self.0 = 0;
+            WIPED.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn should_redact_format() {
+        assert_eq!("sk-123", API_KEY.expose());
+        assert_eq!(1234, PIN.expose().get());
+        assert_eq!("***", API_KEY.to_string());
+        assert_eq!("***", format!("{:?}", *API_KEY));
+        assert_eq!("***", format!("{:?}", *PIN));
+    }
+
+    #[test]
+    #[cfg(feature = "secret")]
+    fn should_zeroize_on_drop() {
+        let secret = Secret::new(Key(7));
+        assert_eq!(7, secret.expose().0);
+        assert!(!WIPED.load(Ordering::SeqCst));
+        drop(secret);
+        assert!(WIPED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn should_read_secret_file() {
+        let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("wildbird_var_secret");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("db_port"), "6543\n").unwrap();
+        assert_eq!(6543, *DB_PORT.expose());
+
+        let secret = Secret::from_file(dir.join("db_port")).unwrap();
+        assert_eq!("6543", secret.expose());
+    }
+
+    #[test]
+    fn should_fallback_when_file_missing() {
+        assert_eq!("from-env", FALLBACK_TOKEN.expose());
+        let missing = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("wildbird_var_secret/missing");
+        assert!(Secret::from_file(missing).is_err());
+    }
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "serde")]
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test var_snapshot --features serde,auto-register
+mod var_snapshot {
+    use serde::Serialize;
+    use serde_json::json;
+    use wildbird::prelude::*;
+    use wildbird::{register_var, snapshot_vars};
+
+    #[derive(Serialize)]
+    struct Limits {
+        requests: u32,
+        burst: u32,
+    }
+
+    #[var]
+    fn limits() -> Limits {
+        Limits { requests: 100, burst: 10 }
+    }
+
+    #[var(name = "REGION")]
+    fn region() -> String {
+        "eu-west".to_string()
+    }
+
+    #[var(secret)]
+    fn api_key() -> String {
+        "sk-123".to_string()
+    }
+
+    // Not Serialize, skipped
+    #[var]
+    fn started() -> std::time::Instant {
+        std::time::Instant::now()
+    }
+
+    #[var(ttl = "10s")]
+    fn pool_size() -> u32 {
+        8
+    }
+
+    #[var(no_arc)]
+    fn zones() -> Vec<String> {
+        vec!["a".to_string(), "b".to_string()]
+    }
+
+    #[var(mutable)]
+    fn max_conn() -> u32 {
+        16
+    }
+
+    #[var(watch)]
+    async fn phase(callback: Callback<String>) {
+        callback.call("ready".to_string());
+    }
+
+    static RETRIES: Lazy<u32> = Lazy::new(|| 3);
+
+    #[test]
+    fn should_serialize_lazy() {
+        static TIMEOUT: Lazy<u64> = Lazy::new(|| 30);
+        assert_eq!("null", serde_json::to_string(&TIMEOUT).unwrap());
+        assert_eq!(30, *TIMEOUT);
+        assert_eq!("30", serde_json::to_string(&TIMEOUT).unwrap());
+    }
+
+    #[test]
+    fn should_snapshot_registered_vars() {
+        register_var("RETRIES", &RETRIES, false);
+        let _ = *RETRIES;
+        let _ = LIMITS.requests;
+        let _ = API_KEY.expose().len();
+        let _ = STARTED.elapsed();
+        let _ = *POOL_SIZE.get();
+        let _ = ZONES.len();
+        *MAX_CONN.write() += 1;
+        let _ = PHASE.get();
+
+        let snapshot = snapshot_vars();
+        assert_eq!(json!(3), snapshot["RETRIES"]);
+        #[cfg(feature = "auto-register")]
+        {
+            assert_eq!(json!({"requests": 100, "burst": 10}), snapshot["LIMITS"]);
+            assert_eq!(json!(null), snapshot["REGION"]);
+            assert_eq!(json!(wildbird::REDACTED), snapshot["API_KEY"]);
+            assert!(snapshot.get("STARTED").is_none());
+            assert_eq!(json!(8), snapshot["POOL_SIZE"]);
+            assert_eq!(json!(["a", "b"]), snapshot["ZONES"]);
+            assert_eq!(json!(17), snapshot["MAX_CONN"]);
+            assert_eq!(json!("ready"), snapshot["PHASE"]);
+            assert!(!snapshot.to_string().contains("sk-123"));
+        }
+    }
+}
@@ -0,0 +1,59 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test var_thread_local
+mod var_thread_local {
+    use std::cell::{Cell, RefCell};
+    use std::panic::catch_unwind;
+    use wildbird::prelude::*;
+
+    static BUILT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    #[var(thread_local)]
+    fn scratch() -> Vec<u8> {
+        BUILT.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Vec::with_capacity(16)
+    }
+
+    #[var(thread_local, name = "SEED")]
+    fn seed() -> Cell<u64> {
+        Cell::new(7)
+    }
+
+    #[var(thread_local)]
+    async fn history() -> RefCell<Vec<String>> {
+        RefCell::new(vec!["start".to_string()])
+    }
+
+    #[var(thread_local)]
+    fn looped() -> u32 {
+        LOOPED.with(|v| *v + 1)
+    }
+
+    #[test]
+    fn should_build_value_per_thread() {
+        assert!(!SCRATCH.is_initialized());
+        SCRATCH.with_mut(|buffer| buffer.push(1));
+        SCRATCH.with_mut(|buffer| buffer.push(2));
+        assert_eq!(vec![1, 2], SCRATCH.with(|buffer| buffer.clone()));
+
+        let other = std::thread::spawn(|| SCRATCH.with(|buffer| buffer.len()));
+        assert_eq!(0, other.join().unwrap());
+        assert_eq!(2, BUILT.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn should_allow_cell_types() {
+        SEED.with(|seed| seed.set(seed.get() * 3));
+        assert_eq!(21, SEED.with(|seed| seed.get()));
+
+        HISTORY.with(|history| history.borrow_mut().push("next".to_string()));
+        assert_eq!(2, HISTORY.with(|history| history.borrow().len()));
+    }
+
+    #[test]
+    fn should_detect_cycle() {
+        let cycle = catch_unwind(|| LOOPED.with(|v| *v)).unwrap_err();
+        assert!(cycle.downcast_ref::<String>().unwrap().contains("Circular dependency"));
+    }
+}
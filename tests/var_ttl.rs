@@ -0,0 +1,136 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test var_ttl
+mod var_ttl {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
+    use std::time::Duration;
+    use wildbird::prelude::*;
+
+    fn wait(millis: u64) {
+        std::thread::sleep(Duration::from_millis(millis));
+    }
+
+    static TOKENS: AtomicU32 = AtomicU32::new(0);
+
+    #[var(ttl = "300ms")]
+    fn token() -> u32 {
+        TOKENS.fetch_add(1, Ordering::SeqCst)
+    }
+
+    static FETCHES: AtomicU32 = AtomicU32::new(0);
+
+    #[var(ttl = "2s", refresh_ahead = "1900ms")]
+    async fn features() -> u32 {
+        wait(20);
+        FETCHES.fetch_add(1, Ordering::SeqCst)
+    }
+
+    static DNS: AtomicU32 = AtomicU32::new(0);
+
+    #[var(ttl = "200ms", name = "DNS_RESULT")]
+    async fn resolve(callback: Callback<u32>) {
+        let attempt = DNS.fetch_add(1, Ordering::SeqCst);
+        if attempt == 1 {
+            panic!("dns timeout");
+        }
+        callback.call(attempt)
+    }
+
+    #[test]
+    fn should_rebuild_expired_value() {
+        assert!(TOKEN.is_expired());
+        assert_eq!(None, TOKEN.try_get());
+        assert_eq!(0, *TOKEN.get());
+        assert_eq!(0, *TOKEN.get());
+        wait(400);
+        assert!(TOKEN.is_expired());
+        assert_eq!(1, *TOKEN.get());
+    }
+
+    #[test]
+    fn should_refresh_in_background() {
+        assert_eq!(0, *FEATURES.get());
+        wait(150);
+        // Old value while refreshing
+        assert_eq!(0, *FEATURES.get());
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while FEATURES.try_get().is_some_and(|value| *value == 0) && std::time::Instant::now() < deadline {
+            wait(10);
+        }
+        assert_eq!(Some(1), FEATURES.try_get().map(|value| *value));
+        assert_eq!(2, FETCHES.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn should_keep_last_good_value() {
+        static ERRORS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+        DNS_RESULT.on_refresh_error(|error| ERRORS.lock().unwrap().push(error.to_string()));
+
+        assert_eq!(0, *DNS_RESULT.get());
+        wait(300);
+        assert_eq!(0, *DNS_RESULT.get());
+        let errors = ERRORS.lock().unwrap().clone();
+        assert_eq!(1, errors.len());
+        assert!(errors[0].contains("dns timeout"), "{}", errors[0]);
+
+        assert_eq!(2, *DNS_RESULT.get());
+    }
+
+    static SESSIONS: AtomicU32 = AtomicU32::new(0);
+
+    #[var(ttl = "200ms", refresh_ahead = "100ms")]
+    fn session() -> u32 {
+        SESSIONS.fetch_add(1, Ordering::SeqCst)
+    }
+
+    #[test]
+    fn should_not_return_expired_value() {
+        assert_eq!(0, *SESSION.get());
+        wait(300);
+        // Expired, rebuilt on access instead of refreshed in background
+        assert_eq!(1, *SESSION.get());
+    }
+
+    static QUOTES: AtomicU32 = AtomicU32::new(0);
+
+    #[var(ttl = "2s", refresh_ahead = "1800ms")]
+    fn quote() -> u32 {
+        let attempt = QUOTES.fetch_add(1, Ordering::SeqCst);
+        if attempt > 0 {
+            panic!("quote service down");
+        }
+        attempt
+    }
+
+    #[test]
+    fn should_delay_failed_background_refresh() {
+        assert_eq!(0, *QUOTE.get());
+        wait(300);
+        assert_eq!(0, *QUOTE.get());
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while QUOTES.load(Ordering::SeqCst) < 2 && std::time::Instant::now() < deadline {
+            wait(10);
+        }
+        wait(50);
+        // Retried after 450ms, not on every read
+        for _ in 0..20 {
+            assert_eq!(0, *QUOTE.get());
+        }
+        wait(50);
+        assert_eq!(2, QUOTES.load(Ordering::SeqCst));
+    }
+
+    #[var(ttl = "1s")]
+    fn looped() -> u32 {
+        *LOOPED.get() + 1
+    }
+
+    #[test]
+    fn should_detect_cycle() {
+        let cycle = std::panic::catch_unwind(|| LOOPED.get()).unwrap_err();
+        let message = cycle.downcast_ref::<String>().unwrap();
+        assert!(message.contains("Circular dependency"), "{message}");
+    }
+}
@@ -0,0 +1,83 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test var_watch
+mod var_watch {
+    use std::sync::{Condvar, Mutex};
+    use wildbird::prelude::*;
+
+    // Publisher waits for the test before each update
+    struct Gate(Mutex<u32>, Condvar);
+
+    impl Gate {
+        const fn new() -> Gate {
+            Gate(Mutex::new(0), Condvar::new())
+        }
+
+        fn open(&self) {
+            *self.0.lock().unwrap() += 1;
+            self.1.notify_all();
+        }
+
+        fn wait(&self, step: u32) {
+            let mut opened = self.0.lock().unwrap();
+            while *opened < step {
+                opened = self.1.wait(opened).unwrap();
+            }
+        }
+    }
+
+    static STATUS_GATE: Gate = Gate::new();
+
+    #[var(watch)]
+    async fn status(callback: Callback<String>) {
+        callback.call("starting".to_string());
+        STATUS_GATE.wait(1);
+        callback.call("listening".to_string());
+        STATUS_GATE.wait(2);
+        callback.call("degraded".to_string());
+    }
+
+    static EVENTS_GATE: Gate = Gate::new();
+
+    #[var(watch, name = "RECONNECTS")]
+    async fn reconnects(callback: Callback<u32>) {
+        for attempt in 0..3 {
+            EVENTS_GATE.wait(attempt);
+            callback.call(attempt);
+        }
+    }
+
+    #[test]
+    fn should_publish_updates() {
+        assert!(STATUS.try_get().is_none());
+        assert_eq!("starting", STATUS.get().as_str());
+
+        let mut receiver = STATUS.subscribe();
+        assert!(!receiver.has_changed());
+        STATUS_GATE.open();
+        assert_eq!("listening", receiver.wait_changed().unwrap().as_str());
+        assert_eq!("listening", STATUS.get().as_str());
+
+        STATUS_GATE.open();
+        assert_eq!("degraded", receiver.wait_changed().unwrap().as_str());
+        assert_eq!(None, receiver.wait_changed());
+        assert_eq!("degraded", receiver.latest().unwrap().as_str());
+    }
+
+    #[tokio::test]
+    async fn should_await_changes() {
+        assert_eq!(0, *RECONNECTS.get());
+        let mut receiver = RECONNECTS.subscribe();
+
+        EVENTS_GATE.open();
+        EVENTS_GATE.open();
+        let mut seen = vec![];
+        while let Some(value) = receiver.changed().await {
+            seen.push(*value);
+        }
+        assert_eq!(Some(&2), seen.last());
+        assert_eq!(2, *RECONNECTS.get());
+        assert_eq!(None, RECONNECTS.changed().await);
+    }
+}
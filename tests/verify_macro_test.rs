@@ -0,0 +1,50 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test verify_macro_test
+mod verify_macro {
+    use wildbird::prelude::*;
+    use wildbird::Verify;
+
+    struct Database {
+        url: String,
+    }
+
+    #[service]
+    impl Database {
+        #[construct]
+        fn connect() -> Self {
+            panic!("connecting to production database")
+        }
+    }
+
+    struct Users {
+        db: Arc<Database>,
+    }
+
+    #[service]
+    impl Users {
+        #[construct]
+        fn new(db: Arc<Database>) -> Self {
+            Users { db }
+        }
+    }
+
+    #[wildbird::verify_test]
+    fn should_verify_with_stubs() -> Verify {
+        wildbird::register(&Users);
+        wildbird::register(&Database);
+        Verify::new().stub(
+            &Database,
+            Database {
+                url: "memory".to_string(),
+            },
+        )
+    }
+
+    #[wildbird::verify_test]
+    #[should_panic(expected = "connecting to production database")]
+    fn should_fail_without_stubs() {
+        wildbird::register(&Users);
+    }
+}
@@ -0,0 +1,106 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test verify_test
+mod verify {
+    use wildbird::prelude::*;
+
+    #[service(construct = "init")]
+    struct Healthy {
+        name: String,
+    }
+
+    impl Healthy {
+        fn init() -> Healthy {
+            Healthy {
+                name: "healthy".to_string(),
+            }
+        }
+    }
+
+    #[service(construct = "init")]
+    struct Broken {}
+
+    impl Broken {
+        fn init() -> Broken {
+            panic!("database offline")
+        }
+    }
+
+    #[service(construct = "init")]
+    struct DependsOnBroken {
+        broken: Arc<Broken>,
+    }
+
+    impl DependsOnBroken {
+        fn init() -> DependsOnBroken {
+            DependsOnBroken { broken: Broken.instance() }
+        }
+    }
+
+    #[service(construct = "init")]
+    struct Ping {
+        pong: Arc<Pong>,
+    }
+
+    impl Ping {
+        fn init() -> Ping {
+            Ping { pong: Pong.instance() }
+        }
+    }
+
+    #[service(construct = "init")]
+    struct Pong {
+        ping: Arc<Ping>,
+    }
+
+    impl Pong {
+        fn init() -> Pong {
+            Pong { ping: Ping.instance() }
+        }
+    }
+
+    #[test]
+    fn should_report_service_graph() {
+        wildbird::register(&Healthy);
+        wildbird::register(&DependsOnBroken);
+        wildbird::register(&Ping);
+        wildbird::register(&Pong);
+        wildbird::register(&Healthy);
+
+        let report = wildbird::verify();
+        println!("{report}");
+        assert!(!report.is_ok());
+        assert_eq!(1, report.resolved.len());
+        assert!(report.resolved[0].ends_with("Healthy"));
+
+        assert_eq!(1, report.cycles.len());
+        assert_eq!(2, report.cycles[0].len());
+
+        assert_eq!(1, report.panics.len());
+        assert_eq!("database offline", report.panics[0].message);
+        // Broken is registered only with auto-register
+        let broken_registered = cfg!(feature = "auto-register");
+        assert_eq!(broken_registered, report.panics[0].type_name.is_some());
+        assert!(report.panics[0].location.as_ref().unwrap().contains("verify_test.rs"));
+
+        assert_eq!(1, report.unresolved.len());
+        assert!(report.unresolved[0].type_name.ends_with("DependsOnBroken"));
+        assert_eq!(report.panics[0].id, report.unresolved[0].cause);
+
+        // Dependencies still resolve after failed verification
+        assert_eq!("healthy", Healthy.name);
+    }
+
+    static VERIFY_IN_INIT: Lazy<bool> = Lazy::new(|| {
+        let _ = wildbird::verify();
+        // Still under construction after verify
+        *VERIFY_IN_INIT
+    });
+
+    #[test]
+    #[should_panic(expected = "Circular dependency")]
+    fn should_keep_construction_stack_of_caller() {
+        let _ = *VERIFY_IN_INIT;
+    }
+}
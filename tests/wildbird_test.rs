@@ -0,0 +1,78 @@
+#![allow(dead_code, non_snake_case)]
+
+/// Only for debugging
+/// use:  cargo expand --test wildbird_test
+mod wildbird_test {
+    use std::sync::Mutex;
+    use wildbird::prelude::*;
+
+    #[service(construct = "init")]
+    struct Journal {
+        entries: Mutex<Vec<String>>,
+    }
+
+    impl Journal {
+        fn init() -> Journal {
+            Journal {
+                entries: Mutex::new(vec![]),
+            }
+        }
+
+        fn write(&self, entry: &str) -> usize {
+            let mut entries = self.entries.lock().unwrap();
+            entries.push(entry.to_string());
+            entries.len()
+        }
+    }
+
+    #[var]
+    fn region() -> String {
+        "eu-west".to_string()
+    }
+
+    #[service(construct = "init")]
+    struct Uploader {
+        region: Arc<String>,
+    }
+
+    impl Uploader {
+        fn init() -> Uploader {
+            Uploader {
+                region: REGION.instance(),
+            }
+        }
+    }
+
+    #[wildbird::testing::test]
+    fn should_get_fresh_graph_a(journal: Arc<Journal>) {
+        assert_eq!(1, journal.write("a"));
+        assert_eq!(2, Journal.write("a"));
+    }
+
+    #[wildbird::testing::test]
+    fn should_get_fresh_graph_b(journal: &Journal) {
+        assert_eq!(1, journal.write("b"));
+        assert_eq!(2, Journal.write("b"));
+    }
+
+    #[wildbird::testing::test(overrides(REGION = "test-region"))]
+    fn should_apply_overrides(uploader: Arc<Uploader>) {
+        assert_eq!("test-region", uploader.region.as_str());
+    }
+
+    #[wildbird::testing::test(flavor = "current_thread")]
+    async fn should_run_async_test(journal: Lazy<Journal>) -> Result<(), String> {
+        assert_eq!(1, journal.write("async"));
+        Ok(())
+    }
+
+    #[cfg(feature = "tokio")]
+    #[wildbird::testing::test(flavor = "multi_thread", worker_threads = 2, overrides(REGION = "spawned"))]
+    async fn should_share_graph_with_workers(journal: Arc<Journal>) {
+        journal.write("main");
+        let spawned = tokio::spawn(async { (Journal.instance(), Uploader.region.to_string()) });
+        let (spawned_journal, region) = spawned.await.unwrap();
+        assert!(Arc::ptr_eq(&journal, &spawned_journal));
+        assert_eq!("spawned", region);
+    }
+}